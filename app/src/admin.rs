@@ -0,0 +1,241 @@
+//! The admin page: instance settings, backups and maintenance.
+
+use leptos::html;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+use crate::api::{get_settings, ImportAll};
+use crate::base_path;
+use crate::config::Config;
+use crate::export::DatabaseSnapshot;
+use crate::toast::use_toasts;
+
+/// The snapshot in the file picked in `input`, or `None` when nothing is
+/// picked.
+async fn read_snapshot(input: NodeRef<html::Input>) -> Result<Option<DatabaseSnapshot>, String> {
+    #[cfg(feature = "hydrate")]
+    {
+        let Some(file) = input
+            .get_untracked()
+            .and_then(|input| input.files())
+            .and_then(|files| files.get(0))
+        else {
+            return Ok(None);
+        };
+        let text = wasm_bindgen_futures::JsFuture::from(file.text())
+            .await
+            .map_err(|err| format!("could not read the file: {err:?}"))?
+            .as_string()
+            .unwrap_or_default();
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|err| format!("not a Seiten snapshot: {err}"))
+    }
+    #[cfg(not(feature = "hydrate"))]
+    {
+        let _ = input;
+        Err("files can only be read in the browser".to_string())
+    }
+}
+
+#[component]
+pub fn AdminPage() -> impl IntoView {
+    let settings = Resource::new(|| (), |_| get_settings());
+
+    view! {
+        <div class="max-w-4xl mx-auto p-4 space-y-4">
+            <h1 class="text-3xl font-bold">"Admin"</h1>
+            <Backup/>
+            <div class="card bg-base-200">
+                <div class="card-body">
+                    <h2 class="card-title">"Maintenance"</h2>
+                    <p>
+                        "Series are purged, with their episodes, from the "
+                        <A href="/library" attr:class="link">"Library"</A>
+                        " page."
+                    </p>
+                </div>
+            </div>
+            <Transition fallback=|| view! { <span class="loading loading-spinner"></span> }>
+                {move || Suspend::new(async move {
+                    match settings.await {
+                        Ok(config) => view! { <Settings config/> }.into_any(),
+                        Err(err) => view! {
+                            <div role="alert" class="alert alert-error">{err.to_string()}</div>
+                        }.into_any(),
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+/// Snapshot download, and restore from an uploaded snapshot.
+#[component]
+fn Backup() -> impl IntoView {
+    let toasts = use_toasts();
+    let restore = ServerAction::<ImportAll>::new();
+    let file = NodeRef::<html::Input>::new();
+    // The snapshot waiting for the user to confirm the restore.
+    let confirming = RwSignal::new(None::<DatabaseSnapshot>);
+
+    Effect::new(move |_| match restore.value().get() {
+        Some(Ok(report)) => toasts.success(format!(
+            "Restored {} series and {} episodes.",
+            report.series, report.episodes,
+        )),
+        Some(Err(err)) => toasts.error(format!("Could not restore the snapshot: {err}")),
+        None => {}
+    });
+
+    let on_restore = move |_| {
+        leptos::task::spawn_local(async move {
+            match read_snapshot(file).await {
+                Ok(Some(payload)) => confirming.set(Some(payload)),
+                Ok(None) => toasts.error("Pick a snapshot file first."),
+                Err(err) => toasts.error(format!("Could not restore the snapshot: {err}")),
+            }
+        });
+    };
+
+    let on_confirm = move |_| {
+        if let Some(payload) = confirming.get_untracked() {
+            restore.dispatch(ImportAll { payload });
+        }
+        confirming.set(None);
+    };
+
+    view! {
+        <div class="card bg-base-200">
+            <div class="card-body gap-4">
+                <h2 class="card-title">"Backup"</h2>
+                <div>
+                    <a
+                        class="btn btn-sm"
+                        href=format!("{}/api/v1/export/snapshot.json", base_path())
+                        rel="external"
+                        download
+                    >
+                        "Download snapshot"
+                    </a>
+                </div>
+                <p class="text-sm opacity-70">
+                    "Restoring replaces every series in the snapshot, matched by slug, "
+                    "including its episodes and overrides. Other series are left alone."
+                </p>
+                <div class="flex flex-wrap gap-2">
+                    <input
+                        type="file"
+                        accept="application/json,.json"
+                        class="file-input file-input-bordered file-input-sm"
+                        node_ref=file
+                    />
+                    <button
+                        class="btn btn-sm btn-warning"
+                        disabled=move || restore.pending().get()
+                        on:click=on_restore
+                    >
+                        "Restore"
+                    </button>
+                </div>
+            </div>
+        </div>
+
+        <dialog class="modal" class:modal-open=move || confirming.with(Option::is_some)>
+            <div class="modal-box">
+                <h3 class="text-lg font-bold">
+                    {move || confirming.with(|snapshot| {
+                        snapshot.as_ref().map(|snapshot| {
+                            format!("Restore {} series?", snapshot.series.len())
+                        })
+                    })}
+                </h3>
+                <p class="py-4">
+                    "Series with the same slug are replaced, with their episodes and overrides. "
+                    "It cannot be undone."
+                </p>
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| confirming.set(None)>"Cancel"</button>
+                    <button class="btn btn-warning" on:click=on_confirm>"Restore"</button>
+                </div>
+            </div>
+        </dialog>
+    }
+}
+
+/// The running configuration, read-only; change it in `config.toml` or the
+/// environment and restart.
+#[component]
+fn Settings(config: Config) -> impl IntoView {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let features = if config.features.is_empty() {
+        "none".to_string()
+    } else {
+        config
+            .features
+            .iter()
+            .map(|(name, on)| format!("{name}: {}", on_off(*on)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let rows = [
+        ("Database", config.database_url),
+        (
+            "Shutdown grace",
+            format!("{}s", config.server.shutdown_grace_secs),
+        ),
+        (
+            "Content-Security-Policy",
+            on_off(config.security.csp).to_string(),
+        ),
+        (
+            "Strict-Transport-Security",
+            on_off(config.security.hsts).to_string(),
+        ),
+        (
+            "Stale after",
+            format!("{} days", config.refresh.stale_after_days),
+        ),
+        (
+            "Refresh interval",
+            format!("{} hours", config.refresh.interval_hours),
+        ),
+        ("User agent", config.scrape.user_agent),
+        (
+            "Request interval",
+            format!("{} ms", config.scrape.min_request_interval_ms),
+        ),
+        (
+            "Fuzzy-match threshold",
+            config.scrape.fuzzy_match_threshold.to_string(),
+        ),
+        ("Providers", config.scrape.providers.join(", ")),
+        ("Features", features),
+    ];
+
+    view! {
+        <div class="card bg-base-200">
+            <div class="card-body">
+                <h2 class="card-title">"Settings"</h2>
+                <p class="text-sm opacity-70">
+                    "Set in config.toml or SEITEN_* environment variables; changes apply on restart."
+                </p>
+                <table class="table">
+                    <tbody>
+                        {rows
+                            .into_iter()
+                            .map(|(label, value)| {
+                                view! {
+                                    <tr>
+                                        <th>{label}</th>
+                                        <td class="font-mono text-sm">{value}</td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,190 @@
+//! Server functions called by the UI.
+
+use leptos::prelude::*;
+use leptos::server_fn::codec::Json;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::dashboard::LibrarySummary;
+#[cfg(feature = "ssr")]
+use crate::db::{db_error, use_db, EpisodeStore, PageRequest, SeriesQuery, SeriesStore};
+use crate::export::{DatabaseSnapshot, FillerRanges, SeriesExport};
+use crate::stats::SeriesStats;
+use crate::types::{EpisodeData, EpisodeEdit, ImportReport, MergeReport, Page, SeriesData};
+
+/// Fails unless `admin.enabled` is set, for server functions that change or
+/// reveal the whole instance.
+#[cfg(feature = "ssr")]
+fn require_admin() -> Result<(), ServerFnError> {
+    if crate::config::use_config().admin.enabled {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(
+            "admin functions are disabled; set admin.enabled in config.toml",
+        ))
+    }
+}
+
+/// One page of the cached series, by title.
+#[server]
+pub async fn list_library(page: u64) -> Result<Page<SeriesData>, ServerFnError> {
+    let db = use_db()?;
+    let query = SeriesQuery {
+        page: PageRequest::new(Some(page), None),
+        ..Default::default()
+    };
+    Ok(SeriesStore::list(&db, &query)
+        .await
+        .map_err(db_error)?
+        .map(SeriesData::from))
+}
+
+/// Library-wide totals and the series most and least recently fetched.
+#[server]
+pub async fn get_dashboard() -> Result<LibrarySummary, ServerFnError> {
+    use crate::config::use_config;
+    use crate::dashboard::DASHBOARD_LIST_LEN;
+    use crate::stats::TypeShare;
+
+    let stale_after_days = use_config().refresh.stale_after_days;
+    let db = use_db()?;
+    let counts = EpisodeStore::count_by_type(&db).await.map_err(db_error)?;
+    let recently_fetched = SeriesStore::recently_fetched(&db, DASHBOARD_LIST_LEN)
+        .await
+        .map_err(db_error)?;
+    let cutoff = chrono::Duration::try_days(stale_after_days)
+        .and_then(|age| chrono::Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| ServerFnError::new("refresh.stale_after_days is out of range"))?;
+    let stale = SeriesStore::stale(
+        &db,
+        cutoff,
+        PageRequest::new(Some(1), Some(DASHBOARD_LIST_LEN)),
+    )
+    .await
+    .map_err(db_error)?;
+
+    Ok(LibrarySummary {
+        series: SeriesStore::count(&db).await.map_err(db_error)?,
+        episodes: counts.iter().map(|(_, count)| count).sum(),
+        types: TypeShare::from_counts(counts.into_iter().map(|(ty, count)| (ty, count as usize))),
+        recently_fetched: recently_fetched.into_iter().map(SeriesData::from).collect(),
+        stale_total: stale.total,
+        stale_after_days,
+        stale: stale.items.into_iter().map(SeriesData::from).collect(),
+    })
+}
+
+/// A series and all of its episodes, for the series page.
+#[server]
+pub async fn get_series(slug: String) -> Result<SeriesExport, ServerFnError> {
+    let db = use_db()?;
+    let series = SeriesStore::find_by_slug(&db, &slug)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServerFnError::new(format!("no series with slug `{slug}`")))?;
+    let episodes = EpisodeStore::for_series(&db, series.id)
+        .await
+        .map_err(db_error)?;
+
+    Ok(SeriesExport {
+        series: series.into(),
+        episodes: episodes.into_iter().map(EpisodeData::from).collect(),
+    })
+}
+
+/// A series' filler episodes as compact ranges (`26-32, 50, 90-110`).
+#[server]
+pub async fn get_filler_ranges(slug: String) -> Result<FillerRanges, ServerFnError> {
+    let db = use_db()?;
+    let series = SeriesStore::find_by_slug(&db, &slug)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServerFnError::new(format!("no series with slug `{slug}`")))?;
+    let episodes = EpisodeStore::for_series(&db, series.id)
+        .await
+        .map_err(db_error)?;
+    let episodes: Vec<_> = episodes.into_iter().map(EpisodeData::from).collect();
+    Ok(FillerRanges::from_episodes(&episodes))
+}
+
+/// Episode counts by type and the longest filler streak for a series.
+#[server]
+pub async fn get_series_stats(slug: String) -> Result<SeriesStats, ServerFnError> {
+    let db = use_db()?;
+    let series = SeriesStore::find_by_slug(&db, &slug)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServerFnError::new(format!("no series with slug `{slug}`")))?;
+    let episodes = EpisodeStore::for_series(&db, series.id)
+        .await
+        .map_err(db_error)?;
+    let episodes: Vec<_> = episodes.into_iter().map(EpisodeData::from).collect();
+    Ok(SeriesStats::from_episodes(&episodes))
+}
+
+/// Saves a manual correction to an episode; see [`EpisodeStore::edit`] for
+/// how text fields are applied.
+#[server]
+pub async fn update_episode(id: Uuid, edit: EpisodeEdit) -> Result<EpisodeData, ServerFnError> {
+    let db = use_db()?;
+    EpisodeStore::edit(&db, id, edit)
+        .await
+        .map_err(db_error)?
+        .map(EpisodeData::from)
+        .ok_or_else(|| ServerFnError::new("that episode no longer exists"))
+}
+
+/// Deletes a series with its episodes, returning how many episodes went
+/// with it.
+#[server]
+pub async fn delete_series(id: Uuid) -> Result<u64, ServerFnError> {
+    require_admin()?;
+    let db = use_db()?;
+    SeriesStore::delete(&db, id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServerFnError::new("that series no longer exists"))
+}
+
+/// Merges a duplicate series (a renamed or mistyped slug) into `target_id`
+/// and deletes it.
+#[server]
+pub async fn merge_series(source_id: Uuid, target_id: Uuid) -> Result<MergeReport, ServerFnError> {
+    require_admin()?;
+    if source_id == target_id {
+        return Err(ServerFnError::new("cannot merge a series into itself"));
+    }
+
+    let db = use_db()?;
+    SeriesStore::merge(&db, source_id, target_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServerFnError::new("both series must exist"))
+}
+
+/// The running configuration, with secrets masked, for the admin page.
+#[server]
+pub async fn get_settings() -> Result<Config, ServerFnError> {
+    require_admin()?;
+    Ok(crate::config::use_config().redacted())
+}
+
+/// Every series, episode and manual correction as one versioned document.
+#[server]
+pub async fn export_all() -> Result<DatabaseSnapshot, ServerFnError> {
+    let db = use_db()?;
+    SeriesStore::export_all(&db).await.map_err(db_error)
+}
+
+/// Restores a snapshot from [`export_all`]. Series in the snapshot replace
+/// the stored ones with the same slug; nothing is written if any part fails.
+#[server(input = Json)]
+pub async fn import_all(payload: DatabaseSnapshot) -> Result<ImportReport, ServerFnError> {
+    require_admin()?;
+    payload.validate().map_err(ServerFnError::new)?;
+
+    let db = use_db()?;
+    SeriesStore::import_all(&db, &payload)
+        .await
+        .map_err(db_error)
+}
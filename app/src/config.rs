@@ -0,0 +1,259 @@
+//! Instance configuration.
+//!
+//! Layered, later sources winning: built-in defaults, then `config.toml` (or
+//! the file named by `SEITEN_CONFIG`), then environment variables. Any key
+//! can be set as `SEITEN_<SECTION>__<KEY>`, e.g. `SEITEN_SCRAPE__USER_AGENT`.
+//! The older `DATABASE_URL`, `CSP_ENABLED` and `HSTS_ENABLED` variables keep
+//! working.
+
+#[cfg(feature = "ssr")]
+use figment::{
+    providers::{Env, Format, Serialized, Toml},
+    Figment,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "ssr")]
+use std::sync::Arc;
+
+/// Config file read when `SEITEN_CONFIG` is not set.
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Largest `refresh.stale_after_days` accepted; about a century.
+pub const MAX_STALE_AFTER_DAYS: i64 = 36_500;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub database_url: String,
+    pub server: ServerConfig,
+    pub security: SecurityConfig,
+    pub admin: AdminConfig,
+    pub refresh: RefreshConfig,
+    pub scrape: ScrapeConfig,
+    /// Switches for experimental subsystems, off unless listed as `true`.
+    pub features: BTreeMap<String, bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// How long a shutdown waits for in-flight requests before giving up
+    /// on them.
+    pub shutdown_grace_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            shutdown_grace_secs: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Emit a Content-Security-Policy header.
+    pub csp: bool,
+    /// Emit Strict-Transport-Security; only for instances served over HTTPS.
+    pub hsts: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            csp: true,
+            hsts: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// Allow snapshot restores, series deletes and merges, and reading the
+    /// settings. There are no accounts yet, so when on, anyone who can reach
+    /// the instance can use them.
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshConfig {
+    /// A series counts as stale once it has gone this long without a fetch.
+    pub stale_after_days: i64,
+    /// How often the scheduled refresh runs.
+    pub interval_hours: u64,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            stale_after_days: 7,
+            interval_hours: 24,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrapeConfig {
+    pub user_agent: String,
+    /// Minimum gap between requests to one upstream host.
+    pub min_request_interval_ms: u64,
+    /// Lowest similarity (0–1) at which a fuzzy title match is accepted
+    /// without review.
+    pub fuzzy_match_threshold: f64,
+    /// Metadata providers to try, in order.
+    pub providers: Vec<String>,
+}
+
+impl Default for ScrapeConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: format!("seiten/{}", env!("CARGO_PKG_VERSION")),
+            min_request_interval_ms: 2000,
+            fuzzy_match_threshold: 0.85,
+            providers: vec!["anidb".to_string()],
+        }
+    }
+}
+
+impl Config {
+    /// Loads the layered configuration described in the module docs.
+    #[cfg(feature = "ssr")]
+    pub fn load() -> Result<Self, Box<figment::Error>> {
+        let file = std::env::var("SEITEN_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.into());
+        let config: Self = Self::figment(&file).extract()?;
+        config.validate().map_err(figment::Error::from)?;
+        Ok(config)
+    }
+
+    /// Rejects values that parse but make no sense, so they fail at startup
+    /// rather than on first use.
+    pub fn validate(&self) -> Result<(), String> {
+        let days = self.refresh.stale_after_days;
+        if !(1..=MAX_STALE_AFTER_DAYS).contains(&days) {
+            return Err(format!(
+                "refresh.stale_after_days must be between 1 and {MAX_STALE_AFTER_DAYS}, got {days}"
+            ));
+        }
+        let threshold = self.scrape.fuzzy_match_threshold;
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(format!(
+                "scrape.fuzzy_match_threshold must be between 0 and 1, got {threshold}"
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "ssr")]
+    fn figment(file: &str) -> Figment {
+        Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::file(file))
+            .merge(Env::raw().only(&["database_url"]))
+            .merge(Env::raw().only(&["csp_enabled", "hsts_enabled"]).map(
+                |key| match key.as_str() {
+                    "csp_enabled" => "security.csp".into(),
+                    _ => "security.hsts".into(),
+                },
+            ))
+            .merge(Env::prefixed("SEITEN_").split("__"))
+    }
+
+    pub fn feature(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+
+    /// A copy safe to show in the UI, with database credentials masked.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if let Some((scheme, rest)) = self.database_url.split_once("://") {
+            if let Some((_, host)) = rest.rsplit_once('@') {
+                config.database_url = format!("{scheme}://***@{host}");
+            }
+        }
+        config
+    }
+}
+
+/// The configuration provided to server functions, or the defaults when
+/// none was (as in store tests).
+#[cfg(feature = "ssr")]
+pub fn use_config() -> Arc<Config> {
+    leptos::prelude::use_context::<Arc<Config>>().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "ssr")]
+    use figment::Jail;
+
+    #[test]
+    #[cfg(feature = "ssr")]
+    // `Jail` closures must return figment's own (large) error type.
+    #[allow(clippy::result_large_err)]
+    fn file_then_environment() {
+        Jail::expect_with(|jail| {
+            jail.create_file(
+                "seiten.toml",
+                r#"
+                    database_url = "sqlite://file.db"
+
+                    [refresh]
+                    stale_after_days = 3
+
+                    [features]
+                    semantic_search = true
+                "#,
+            )?;
+            jail.set_env("DATABASE_URL", "sqlite://env.db");
+            jail.set_env("HSTS_ENABLED", "true");
+            jail.set_env("SEITEN_SCRAPE__USER_AGENT", "test-agent");
+
+            let config: Config = Config::figment("seiten.toml").extract()?;
+            assert_eq!(config.database_url, "sqlite://env.db");
+            assert_eq!(config.refresh.stale_after_days, 3);
+            assert_eq!(config.refresh.interval_hours, 24);
+            assert!(config.security.csp);
+            assert!(config.security.hsts);
+            assert_eq!(config.scrape.user_agent, "test-agent");
+            assert!(config.feature("semantic_search"));
+            assert!(!config.feature("activitypub"));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        assert_eq!(Config::default().validate(), Ok(()));
+
+        for days in [-1, 0, MAX_STALE_AFTER_DAYS + 1, i64::MAX] {
+            let mut config = Config::default();
+            config.refresh.stale_after_days = days;
+            assert!(config.validate().is_err(), "{days}");
+        }
+        for threshold in [-0.1, 1.5, f64::NAN] {
+            let mut config = Config::default();
+            config.scrape.fuzzy_match_threshold = threshold;
+            assert!(config.validate().is_err(), "{threshold}");
+        }
+    }
+
+    #[test]
+    fn redacted_masks_database_credentials() {
+        let config = Config {
+            database_url: "postgres://seiten:secret@db/seiten".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.redacted().database_url, "postgres://***@db/seiten");
+
+        let config = Config {
+            database_url: "sqlite://db.sqlite?mode=rwc".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.redacted(), config);
+    }
+}
@@ -0,0 +1,135 @@
+//! The dashboard: a summary of the whole library.
+
+use leptos::prelude::*;
+use leptos_router::components::A;
+use serde::{Deserialize, Serialize};
+
+use crate::api::get_dashboard;
+use crate::stats::{TypeBar, TypeShare};
+use crate::types::SeriesData;
+
+/// How many series the recent and stale lists show.
+pub const DASHBOARD_LIST_LEN: u64 = 5;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibrarySummary {
+    pub series: u64,
+    pub episodes: u64,
+    /// Episodes by effective type, in `EpisodeType::ALL` order.
+    pub types: Vec<TypeShare>,
+    pub recently_fetched: Vec<SeriesData>,
+    /// The stalest series, never-fetched ones first.
+    pub stale: Vec<SeriesData>,
+    /// How many series are stale in total.
+    pub stale_total: u64,
+    /// The configured `refresh.stale_after_days`.
+    pub stale_after_days: i64,
+}
+
+#[component]
+pub fn DashboardPage() -> impl IntoView {
+    let summary = Resource::new(|| (), |_| get_dashboard());
+
+    view! {
+        <div class="max-w-5xl mx-auto p-4 space-y-4">
+            <h1 class="text-3xl font-bold">"Dashboard"</h1>
+            <Transition fallback=|| view! { <span class="loading loading-spinner"></span> }>
+                {move || Suspend::new(async move {
+                    match summary.await {
+                        Ok(summary) => view! { <Summary summary/> }.into_any(),
+                        Err(err) => view! {
+                            <div role="alert" class="alert alert-error">{err.to_string()}</div>
+                        }.into_any(),
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+#[component]
+fn Summary(summary: LibrarySummary) -> impl IntoView {
+    let type_stats = summary
+        .types
+        .iter()
+        .map(|share| {
+            view! {
+                <div class="stat">
+                    <div class="stat-title">{share.episode_type.as_str()}</div>
+                    <div class="stat-value text-2xl">{share.count}</div>
+                    <div class="stat-desc">{format!("{:.0}%", share.percent)}</div>
+                </div>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="card bg-base-200">
+            <div class="card-body gap-4">
+                <div class="stats stats-vertical lg:stats-horizontal bg-base-200">
+                    <div class="stat">
+                        <div class="stat-title">"Series"</div>
+                        <div class="stat-value text-2xl">{summary.series}</div>
+                    </div>
+                    <div class="stat">
+                        <div class="stat-title">"Episodes"</div>
+                        <div class="stat-value text-2xl">{summary.episodes}</div>
+                    </div>
+                </div>
+                <div class="stats stats-vertical lg:stats-horizontal bg-base-200">{type_stats}</div>
+                <TypeBar types=summary.types/>
+            </div>
+        </div>
+        <div class="grid gap-4 md:grid-cols-2">
+            <SeriesList
+                title="Recently fetched".to_string()
+                empty="Nothing fetched yet."
+                series=summary.recently_fetched
+            />
+            <SeriesList
+                title=format!(
+                    "Needs a refresh ({}, not fetched in {} days)",
+                    summary.stale_total,
+                    summary.stale_after_days,
+                )
+                empty="Everything is up to date."
+                series=summary.stale
+            />
+        </div>
+    }
+}
+
+#[component]
+fn SeriesList(title: String, empty: &'static str, series: Vec<SeriesData>) -> impl IntoView {
+    let rows = if series.is_empty() {
+        view! { <li class="opacity-70">{empty}</li> }.into_any()
+    } else {
+        series
+            .into_iter()
+            .map(|item| {
+                let fetched = item.last_fetched.map_or_else(
+                    || "never".to_string(),
+                    |at| at.format("%Y-%m-%d").to_string(),
+                );
+                view! {
+                    <li class="flex justify-between gap-2">
+                        <A href=format!("/series/{}", item.slug) attr:class="link link-hover">
+                            {item.title}
+                        </A>
+                        <span class="text-sm opacity-70">{fetched}</span>
+                    </li>
+                }
+            })
+            .collect_view()
+            .into_any()
+    };
+
+    view! {
+        <div class="card bg-base-200">
+            <div class="card-body">
+                <h2 class="card-title">{title}</h2>
+                <ul class="space-y-1">{rows}</ul>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,1464 @@
+//! Database connection and query helpers.
+
+use chrono::{DateTime, Local, Utc};
+use entity::{episode, permalink_alias, prelude::*, series};
+use leptos::prelude::{use_context, ServerFnError};
+use sea_orm::{
+    prelude::{Expr, Uuid},
+    sea_query::{ExprTrait, Func, LikeExpr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, Database, DatabaseConnection, DbErr,
+    EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, Set,
+    TransactionSession, TransactionTrait,
+};
+use std::{collections::HashMap, str::FromStr};
+
+use crate::export::{DatabaseSnapshot, PermalinkAliasExport, SeriesExport};
+use crate::types::{
+    decode_cursor, episode_permalink, EpisodeData, EpisodeEdit, EpisodeType, ImportReport,
+    MergeReport, Page,
+};
+use crate::validation::{clamp_page, clamp_per_page};
+
+#[cfg(test)]
+pub mod testing;
+
+pub async fn connect(url: &str) -> Result<DatabaseConnection, DbErr> {
+    Database::connect(url).await
+}
+
+/// The connection the server provides to server functions.
+pub fn use_db() -> Result<DatabaseConnection, ServerFnError> {
+    use_context::<DatabaseConnection>()
+        .ok_or_else(|| ServerFnError::new("database connection is not available"))
+}
+
+/// Logs a store error in full and returns a generic one for the browser,
+/// since database errors can carry SQL, table names or connection details.
+pub fn db_error(err: DbErr) -> ServerFnError {
+    log::error!("database error: {err}");
+    ServerFnError::new("internal server error")
+}
+
+/// Creates or updates the tables for every entity in the `entity` crate.
+pub async fn sync_schema(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.get_schema_registry("entity::*").sync(db).await
+}
+
+/// Page window shared by all list queries. `page` is 1-based.
+#[derive(Clone, Copy, Debug)]
+pub struct PageRequest {
+    pub page: u64,
+    pub per_page: u64,
+}
+
+impl PageRequest {
+    /// Clamps user-provided values into the supported range.
+    pub fn new(page: Option<u64>, per_page: Option<u64>) -> Self {
+        Self {
+            page: clamp_page(page),
+            per_page: clamp_per_page(per_page),
+        }
+    }
+
+    /// Like [`PageRequest::new`], but a `cursor` from a previous
+    /// [`Page::next_cursor`] takes precedence over `page`.
+    pub fn from_params(
+        page: Option<u64>,
+        per_page: Option<u64>,
+        cursor: Option<&str>,
+    ) -> Result<Self, String> {
+        let page = match cursor.filter(|cursor| !cursor.is_empty()) {
+            Some(cursor) => Some(decode_cursor(cursor)?),
+            None => page,
+        };
+        Ok(Self::new(page, per_page))
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+/// A `?sort=` value: a field name, optionally prefixed with `-` for
+/// descending order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sort<F> {
+    pub field: F,
+    pub descending: bool,
+}
+
+impl<F> Sort<F> {
+    fn order(&self) -> Order {
+        if self.descending {
+            Order::Desc
+        } else {
+            Order::Asc
+        }
+    }
+}
+
+impl<F: FromStr<Err = String>> FromStr for Sort<F> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, descending) = match s.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (s, false),
+        };
+        Ok(Self {
+            field: field.parse()?,
+            descending,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SeriesSortField {
+    #[default]
+    Title,
+    Slug,
+    LastFetched,
+}
+
+impl FromStr for SeriesSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "slug" => Ok(Self::Slug),
+            "last_fetched" => Ok(Self::LastFetched),
+            _ => Err(format!("cannot sort series by `{s}`")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EpisodeSortField {
+    #[default]
+    Number,
+    Title,
+    Type,
+}
+
+impl FromStr for EpisodeSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "number" => Ok(Self::Number),
+            "title" => Ok(Self::Title),
+            "type" => Ok(Self::Type),
+            _ => Err(format!("cannot sort episodes by `{s}`")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SeriesQuery {
+    pub page: PageRequest,
+    pub sort: Option<Sort<SeriesSortField>>,
+    /// Case-insensitive substring match on title or slug.
+    pub q: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EpisodeQuery {
+    pub page: PageRequest,
+    pub sort: Option<Sort<EpisodeSortField>>,
+    /// Only episodes of the series with this slug.
+    pub series: Option<String>,
+    pub episode_type: Option<EpisodeType>,
+    /// Case-insensitive substring match on the episode title.
+    pub q: Option<String>,
+}
+
+/// Case-insensitive substring match that behaves the same on SQLite and
+/// Postgres: plain `LIKE` is case-insensitive only on SQLite. `%` and `_` in
+/// `q` match themselves rather than acting as wildcards.
+///
+/// Only ASCII letters are folded. SQLite's `LOWER()` leaves everything else
+/// alone, so `q` is lowered the same way; `é` and `É` stay distinct there,
+/// while Postgres folds the column side per its locale.
+fn contains_ci<C: ColumnTrait>(column: C, q: &str) -> Expr {
+    let escaped = q
+        .to_ascii_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = LikeExpr::new(format!("%{escaped}%")).escape('\\');
+    Expr::expr(Func::lower(Expr::col(column))).like(pattern)
+}
+
+/// Points a permalink that is going away at `episode_id`, replacing any
+/// older alias with the same permalink.
+async fn add_permalink_alias<C: ConnectionTrait>(
+    db: &C,
+    permalink: Option<String>,
+    episode_id: Uuid,
+) -> Result<(), DbErr> {
+    let Some(permalink) = permalink else {
+        return Ok(());
+    };
+    let alias = permalink_alias::ActiveModel {
+        permalink: Set(permalink),
+        episode_id: Set(episode_id),
+    };
+    PermalinkAlias::insert(alias)
+        .on_conflict(
+            OnConflict::column(permalink_alias::Column::Permalink)
+                .update_column(permalink_alias::Column::EpisodeId)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    Ok(())
+}
+
+/// An episode's user override if set, otherwise its scraped type.
+fn effective_type() -> Expr {
+    Expr::expr(Func::coalesce([
+        Expr::col((Episode, episode::Column::OverrideType)),
+        Expr::col((Episode, episode::Column::EpisodeType)),
+    ]))
+}
+
+async fn fetch_page<C, E>(
+    db: &C,
+    select: Select<E>,
+    page: PageRequest,
+) -> Result<Page<E::Model>, DbErr>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let paginator = select.paginate(db, page.per_page);
+    let total = paginator.num_items().await?;
+    let items = paginator.fetch_page(page.page - 1).await?;
+
+    Ok(Page::new(items, page.page, page.per_page, total))
+}
+
+pub struct SeriesStore;
+
+impl SeriesStore {
+    pub async fn find_by_slug<C: ConnectionTrait>(
+        db: &C,
+        slug: &str,
+    ) -> Result<Option<series::Model>, DbErr> {
+        Series::find()
+            .filter(series::Column::Slug.eq(slug))
+            .one(db)
+            .await
+    }
+
+    /// The series with `slug`, inserted with `title` if it does not exist
+    /// yet. An existing series keeps its title.
+    pub async fn find_or_create<C: ConnectionTrait>(
+        db: &C,
+        slug: &str,
+        title: &str,
+    ) -> Result<series::Model, DbErr> {
+        if let Some(series) = Self::find_by_slug(db, slug).await? {
+            return Ok(series);
+        }
+
+        series::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            slug: Set(slug.to_string()),
+            title: Set(title.to_string()),
+            last_fetched: Set(None),
+        }
+        .insert(db)
+        .await
+    }
+
+    /// Removes the series and everything that belongs to it in one
+    /// transaction. Returns how many episodes were deleted, or `None` if
+    /// there was no such series.
+    pub async fn delete<C: TransactionTrait>(db: &C, id: Uuid) -> Result<Option<u64>, DbErr> {
+        let txn = db.begin().await?;
+
+        let episodes = Episode::delete_many()
+            .filter(episode::Column::ShowId.eq(id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let series = Series::delete_by_id(id).exec(&txn).await?.rows_affected;
+        if series == 0 {
+            txn.rollback().await?;
+            return Ok(None);
+        }
+
+        txn.commit().await?;
+        Ok(Some(episodes))
+    }
+
+    /// Folds `source` into `target` and deletes `source`, in one
+    /// transaction. Episodes only `source` has are moved over; for numbers
+    /// both have, the target's row is kept and only borrows the source's
+    /// title, type, override or notes where its own is missing or
+    /// `Unknown`. Source overrides and notes that lose to the target's own
+    /// are listed in [`MergeReport::discarded`]. Moved episodes take the
+    /// target's permalinks, and the source's become aliases so old links
+    /// still resolve. Returns `None` if either series does not exist, and an
+    /// error if both ids are the same series.
+    pub async fn merge<C: TransactionTrait>(
+        db: &C,
+        source_id: Uuid,
+        target_id: Uuid,
+    ) -> Result<Option<MergeReport>, DbErr> {
+        if source_id == target_id {
+            return Err(DbErr::Custom("cannot merge a series into itself".to_string()));
+        }
+
+        let txn = db.begin().await?;
+
+        let (Some(source), Some(target)) = (
+            Series::find_by_id(source_id).one(&txn).await?,
+            Series::find_by_id(target_id).one(&txn).await?,
+        ) else {
+            txn.rollback().await?;
+            return Ok(None);
+        };
+
+        let existing: HashMap<i32, episode::Model> = EpisodeStore::for_series(&txn, target.id)
+            .await?
+            .into_iter()
+            .map(|episode| (episode.episode_num, episode))
+            .collect();
+
+        let mut report = MergeReport::default();
+        for episode in EpisodeStore::for_series(&txn, source.id).await? {
+            // The source slug may be scraped again, which would hand out the
+            // same permalinks; the old ones live on as aliases instead.
+            let old_permalink = episode.permalink.clone();
+            let Some(kept) = existing.get(&episode.episode_num) else {
+                let id = episode.id;
+                let mut moved: episode::ActiveModel = episode.into();
+                moved.show_id = Set(target.id);
+                let permalink = episode_permalink(&target.slug, *moved.episode_num.as_ref());
+                moved.permalink = Set(Some(permalink));
+                moved.update(&txn).await?;
+                add_permalink_alias(&txn, old_permalink, id).await?;
+                report.moved += 1;
+                continue;
+            };
+
+            let mut discarded = false;
+            let mut kept: episode::ActiveModel = kept.clone().into();
+            if kept.title.as_ref().is_none() && episode.title.is_some() {
+                kept.title = Set(episode.title.clone());
+                // A hand-edited title stays protected from re-scrapes.
+                if episode.edited_by_user {
+                    kept.edited_by_user = Set(true);
+                }
+            }
+            if *kept.episode_type.as_ref() == episode::EpisodeType::Unknown {
+                kept.episode_type = Set(episode.episode_type.clone());
+            }
+            if kept.override_type.as_ref().is_none() {
+                if episode.override_type.is_some() {
+                    kept.override_type = Set(episode.override_type.clone());
+                }
+            } else if episode.override_type.is_some()
+                && *kept.override_type.as_ref() != episode.override_type
+            {
+                discarded = true;
+            }
+            if kept.notes.as_ref().is_none() {
+                if episode.notes.is_some() {
+                    kept.notes = Set(episode.notes.clone());
+                }
+            } else if episode.notes.is_some() && *kept.notes.as_ref() != episode.notes {
+                discarded = true;
+            }
+            if discarded {
+                report.discarded.push(episode.episode_num);
+            }
+            let kept_id = *kept.id.as_ref();
+            if kept.is_changed() {
+                kept.update(&txn).await?;
+            }
+            PermalinkAlias::update_many()
+                .col_expr(permalink_alias::Column::EpisodeId, Expr::value(kept_id))
+                .filter(permalink_alias::Column::EpisodeId.eq(episode.id))
+                .exec(&txn)
+                .await?;
+            Episode::delete_by_id(episode.id).exec(&txn).await?;
+            add_permalink_alias(&txn, old_permalink, kept_id).await?;
+            report.collisions += 1;
+        }
+
+        if source.last_fetched > target.last_fetched {
+            let mut target: series::ActiveModel = target.into();
+            target.last_fetched = Set(source.last_fetched);
+            target.update(&txn).await?;
+        }
+        Series::delete_by_id(source.id).exec(&txn).await?;
+
+        txn.commit().await?;
+        Ok(Some(report))
+    }
+
+    /// Every series with its episodes, by title, and the permalink aliases
+    /// left behind by merges.
+    pub async fn export_all<C: ConnectionTrait>(db: &C) -> Result<DatabaseSnapshot, DbErr> {
+        let mut exports = Vec::new();
+        for series in Self::all(db).await? {
+            let episodes = EpisodeStore::for_series(db, series.id).await?;
+            exports.push(SeriesExport {
+                series: series.into(),
+                episodes: episodes.into_iter().map(EpisodeData::from).collect(),
+            });
+        }
+
+        let aliases = PermalinkAlias::find()
+            .find_also_related(Episode)
+            .order_by_asc(permalink_alias::Column::Permalink)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(alias, episode)| {
+                Some(PermalinkAliasExport {
+                    permalink: alias.permalink,
+                    episode_permalink: episode?.permalink?,
+                })
+            })
+            .collect();
+        Ok(DatabaseSnapshot::new(exports, aliases))
+    }
+
+    /// Restores a snapshot in one transaction. Each series replaces the
+    /// stored series with the same slug, episodes included; series missing
+    /// from the snapshot are left alone. Rows get fresh ids, but permalinks
+    /// and their aliases carry over. Aliases whose episode is not stored
+    /// after the restore are skipped.
+    pub async fn import_all<C: TransactionTrait>(
+        db: &C,
+        snapshot: &DatabaseSnapshot,
+    ) -> Result<ImportReport, DbErr> {
+        let txn = db.begin().await?;
+        let mut report = ImportReport::default();
+
+        for export in &snapshot.series {
+            let data = &export.series;
+            let series = Self::find_or_create(&txn, &data.slug, &data.title).await?;
+            let mut series: series::ActiveModel = series.into();
+            series.title = Set(data.title.clone());
+            series.last_fetched = Set(data.last_fetched.map(|at| at.with_timezone(&Local)));
+            let series = series.update(&txn).await?;
+
+            Episode::delete_many()
+                .filter(episode::Column::ShowId.eq(series.id))
+                .exec(&txn)
+                .await?;
+            if !export.episodes.is_empty() {
+                let models = export.episodes.iter().map(|episode| {
+                    let permalink = if episode.permalink.is_empty() {
+                        episode_permalink(&series.slug, episode.number)
+                    } else {
+                        episode.permalink.clone()
+                    };
+                    // `episode_type` is the effective type; the scraped one
+                    // is only reported separately when overridden.
+                    let scraped = episode.overridden_from.unwrap_or(episode.episode_type);
+                    episode::ActiveModel {
+                        id: Set(Uuid::new_v4()),
+                        show_id: Set(series.id),
+                        episode_num: Set(episode.number),
+                        episode_type: Set(scraped.into()),
+                        override_type: Set(episode
+                            .overridden_from
+                            .map(|_| episode.episode_type.into())),
+                        title: Set(episode.title.clone()),
+                        notes: Set(episode.notes.clone()),
+                        permalink: Set(Some(permalink)),
+                        edited_by_user: Set(episode.edited_by_user),
+                    }
+                });
+                Episode::insert_many(models).exec(&txn).await?;
+            }
+
+            report.series += 1;
+            report.episodes += export.episodes.len() as u64;
+        }
+
+        for alias in &snapshot.aliases {
+            let episode = Episode::find()
+                .filter(episode::Column::Permalink.eq(&alias.episode_permalink))
+                .one(&txn)
+                .await?;
+            if let Some(episode) = episode {
+                add_permalink_alias(&txn, Some(alias.permalink.clone()), episode.id).await?;
+            }
+        }
+
+        txn.commit().await?;
+        Ok(report)
+    }
+
+    pub async fn all<C: ConnectionTrait>(db: &C) -> Result<Vec<series::Model>, DbErr> {
+        Series::find()
+            .order_by_asc(series::Column::Title)
+            .all(db)
+            .await
+    }
+
+    pub async fn count<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        Series::find().count(db).await
+    }
+
+    /// The `limit` most recently fetched series, newest first.
+    pub async fn recently_fetched<C: ConnectionTrait>(
+        db: &C,
+        limit: u64,
+    ) -> Result<Vec<series::Model>, DbErr> {
+        Series::find()
+            .filter(series::Column::LastFetched.is_not_null())
+            .order_by_desc(series::Column::LastFetched)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Series never fetched, or last fetched before `cutoff`, oldest first.
+    pub async fn stale<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+        page: PageRequest,
+    ) -> Result<Page<series::Model>, DbErr> {
+        let select = Series::find()
+            .filter(
+                Condition::any()
+                    .add(series::Column::LastFetched.is_null())
+                    .add(series::Column::LastFetched.lt(cutoff.with_timezone(&Local))),
+            )
+            .order_by_asc(series::Column::LastFetched)
+            .order_by_asc(series::Column::Title);
+        fetch_page(db, select, page).await
+    }
+
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        query: &SeriesQuery,
+    ) -> Result<Page<series::Model>, DbErr> {
+        let mut select = Series::find();
+
+        if let Some(q) = query.q.as_deref().filter(|q| !q.is_empty()) {
+            select = select.filter(
+                Condition::any()
+                    .add(contains_ci(series::Column::Title, q))
+                    .add(contains_ci(series::Column::Slug, q)),
+            );
+        }
+
+        let sort = query.sort.unwrap_or_default();
+        let column = match sort.field {
+            SeriesSortField::Title => series::Column::Title,
+            SeriesSortField::Slug => series::Column::Slug,
+            SeriesSortField::LastFetched => series::Column::LastFetched,
+        };
+        select = select
+            .order_by(column, sort.order())
+            .order_by_asc(series::Column::Id);
+
+        fetch_page(db, select, query.page).await
+    }
+}
+
+/// An episode to insert with [`EpisodeStore::create_many`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewEpisode {
+    pub number: i32,
+    pub episode_type: EpisodeType,
+    pub title: Option<String>,
+}
+
+pub struct EpisodeStore;
+
+impl EpisodeStore {
+    pub async fn for_series<C: ConnectionTrait>(
+        db: &C,
+        series_id: Uuid,
+    ) -> Result<Vec<episode::Model>, DbErr> {
+        Episode::find()
+            .filter(episode::Column::ShowId.eq(series_id))
+            .order_by_asc(episode::Column::EpisodeNum)
+            .all(db)
+            .await
+    }
+
+    /// Episode counts across the library by effective type, in
+    /// `EpisodeType::ALL` order, including types with no episodes.
+    pub async fn count_by_type<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<Vec<(EpisodeType, u64)>, DbErr> {
+        let rows: Vec<(entity::episode::EpisodeType, i64)> = Episode::find()
+            .select_only()
+            .column_as(effective_type(), "episode_type")
+            .column_as(episode::Column::Id.count(), "count")
+            .group_by(effective_type())
+            .into_tuple()
+            .all(db)
+            .await?;
+        let counts: HashMap<EpisodeType, u64> = rows
+            .into_iter()
+            .map(|(ty, count)| (ty.into(), count as u64))
+            .collect();
+
+        Ok(EpisodeType::ALL
+            .into_iter()
+            .map(|ty| (ty, counts.get(&ty).copied().unwrap_or(0)))
+            .collect())
+    }
+
+    /// Inserts `episodes` for the series, or updates the scraped type and
+    /// title of those whose number already exists, in one statement backed by
+    /// the unique `(show_id, episode_num)` index. User overrides live in
+    /// their own columns, and titles a user has edited are kept. Returns how
+    /// many rows were written.
+    pub async fn create_many<C: ConnectionTrait>(
+        db: &C,
+        series_id: Uuid,
+        episodes: &[NewEpisode],
+    ) -> Result<u64, DbErr> {
+        if episodes.is_empty() {
+            return Ok(0);
+        }
+        let series = Series::find_by_id(series_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("series {series_id}")))?;
+
+        let models = episodes.iter().map(|episode| episode::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            show_id: Set(series_id),
+            episode_num: Set(episode.number),
+            episode_type: Set(episode.episode_type.into()),
+            title: Set(episode.title.clone()),
+            permalink: Set(Some(episode_permalink(&series.slug, episode.number))),
+            ..Default::default()
+        });
+        Episode::insert_many(models)
+            .on_conflict(
+                OnConflict::columns([episode::Column::ShowId, episode::Column::EpisodeNum])
+                    .update_column(episode::Column::EpisodeType)
+                    .value(
+                        episode::Column::Title,
+                        Expr::case(
+                            Expr::col((Episode, episode::Column::EditedByUser)),
+                            Expr::col((Episode, episode::Column::Title)),
+                        )
+                        .finally(Expr::col(("excluded", episode::Column::Title))),
+                    )
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+    }
+
+    /// The episode with this permalink, or the one it was merged into.
+    pub async fn find_by_permalink<C: ConnectionTrait>(
+        db: &C,
+        permalink: &str,
+    ) -> Result<Option<episode::Model>, DbErr> {
+        let episode = Episode::find()
+            .filter(episode::Column::Permalink.eq(permalink))
+            .one(db)
+            .await?;
+        if episode.is_some() {
+            return Ok(episode);
+        }
+
+        let Some(alias) = PermalinkAlias::find_by_id(permalink).one(db).await? else {
+            return Ok(None);
+        };
+        Episode::find_by_id(alias.episode_id).one(db).await
+    }
+
+    /// Gives episodes written before permalinks existed their permalink.
+    /// Returns how many were filled in.
+    pub async fn backfill_permalinks<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        let missing = Episode::find()
+            .find_also_related(Series)
+            .filter(episode::Column::Permalink.is_null())
+            .all(db)
+            .await?;
+
+        let mut filled = 0;
+        for (episode, series) in missing {
+            let Some(series) = series else {
+                continue;
+            };
+            let permalink = episode_permalink(&series.slug, episode.episode_num);
+            let mut episode: episode::ActiveModel = episode.into();
+            episode.permalink = Set(Some(permalink));
+            episode.update(db).await?;
+            filled += 1;
+        }
+        Ok(filled)
+    }
+
+    /// Applies a manual correction. The type goes into `override_type`.
+    /// `title` and `notes` are left alone when `None` and cleared when
+    /// blank. Changing the title marks the episode as edited, so
+    /// [`EpisodeStore::create_many`] keeps it; clearing the title unmarks
+    /// it. Notes never touch the flag, since re-scrapes do not write them.
+    /// Returns `None` if there is no such episode.
+    pub async fn edit<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        edit: EpisodeEdit,
+    ) -> Result<Option<episode::Model>, DbErr> {
+        let Some(episode) = Episode::find_by_id(id).one(db).await? else {
+            return Ok(None);
+        };
+
+        let blank_to_none = |value: String| Some(value).filter(|value| !value.trim().is_empty());
+        let title = edit.title.map_or_else(|| episode.title.clone(), blank_to_none);
+        let notes = edit.notes.map_or_else(|| episode.notes.clone(), blank_to_none);
+        let edited_by_user = if title != episode.title {
+            title.is_some()
+        } else {
+            episode.edited_by_user
+        };
+
+        let mut episode: episode::ActiveModel = episode.into();
+        episode.title = Set(title);
+        episode.override_type = Set(edit.override_type.map(Into::into));
+        episode.notes = Set(notes);
+        episode.edited_by_user = Set(edited_by_user);
+        episode.update(db).await.map(Some)
+    }
+
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        query: &EpisodeQuery,
+    ) -> Result<Page<episode::Model>, DbErr> {
+        let mut select = Episode::find();
+
+        if let Some(slug) = query.series.as_deref() {
+            let Some(series) = SeriesStore::find_by_slug(db, slug).await? else {
+                return Ok(Page::empty(query.page.page, query.page.per_page));
+            };
+            select = select.filter(episode::Column::ShowId.eq(series.id));
+        }
+        if let Some(ty) = query.episode_type {
+            select = select.filter(
+                effective_type().eq(entity::episode::EpisodeType::from(ty)),
+            );
+        }
+        if let Some(q) = query.q.as_deref().filter(|q| !q.is_empty()) {
+            select = select.filter(contains_ci(episode::Column::Title, q));
+        }
+
+        let sort = query.sort.unwrap_or_default();
+        let column = match sort.field {
+            EpisodeSortField::Number => Expr::col(episode::Column::EpisodeNum),
+            EpisodeSortField::Title => Expr::col(episode::Column::Title),
+            EpisodeSortField::Type => effective_type(),
+        };
+        select = select
+            .order_by(column, sort.order())
+            .order_by_asc(episode::Column::ShowId)
+            .order_by_asc(episode::Column::EpisodeNum);
+
+        fetch_page(db, select, query.page).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{memory_db, SeriesFixture};
+    use super::*;
+    use crate::types::EpisodeData;
+    use crate::validation::{MAX_PAGE, MAX_PER_PAGE};
+
+    #[tokio::test]
+    async fn find_or_create_inserts_once() {
+        let db = memory_db().await;
+
+        let created = SeriesStore::find_or_create(&db, "one-piece", "One Piece")
+            .await
+            .unwrap();
+        let found = SeriesStore::find_or_create(&db, "one-piece", "ONE PIECE")
+            .await
+            .unwrap();
+
+        assert_eq!(created, found);
+        assert_eq!(found.title, "One Piece");
+        assert_eq!(SeriesStore::all(&db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn delete_removes_series_and_episodes() {
+        let db = memory_db().await;
+        let doomed = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=3, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        let kept = SeriesFixture::new("bleach", "Bleach")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(SeriesStore::delete(&db, doomed.id).await.unwrap(), Some(3));
+        assert_eq!(SeriesStore::delete(&db, doomed.id).await.unwrap(), None);
+
+        assert!(EpisodeStore::for_series(&db, doomed.id).await.unwrap().is_empty());
+        assert_eq!(EpisodeStore::for_series(&db, kept.id).await.unwrap().len(), 2);
+        assert_eq!(SeriesStore::all(&db).await.unwrap(), [kept]);
+    }
+
+    #[tokio::test]
+    async fn merge_moves_episodes_and_resolves_collisions() {
+        let db = memory_db().await;
+        let target = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=2, EpisodeType::Unknown)
+            .insert(&db)
+            .await
+            .unwrap();
+        let source = SeriesFixture::new("narutoo", "Naruto")
+            .episode(2, EpisodeType::Filler, "Konohamaru")
+            .episode(3, EpisodeType::Canon, "Sasuke and Sakura")
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let report = SeriesStore::merge(&db, source.id, target.id).await.unwrap();
+
+        assert_eq!(
+            report,
+            Some(MergeReport {
+                moved: 1,
+                collisions: 1,
+                discarded: Vec::new(),
+            })
+        );
+        assert_eq!(SeriesStore::find_by_slug(&db, "narutoo").await.unwrap(), None);
+        let episodes = EpisodeStore::for_series(&db, target.id).await.unwrap();
+        let numbers: Vec<_> = episodes.iter().map(|ep| ep.episode_num).collect();
+        assert_eq!(numbers, [1, 2, 3]);
+        assert_eq!(episodes[1].episode_type, entity::episode::EpisodeType::Filler);
+        assert_eq!(episodes[1].title.as_deref(), Some("Konohamaru"));
+    }
+
+    #[tokio::test]
+    async fn merge_into_itself_is_rejected() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+
+        assert!(SeriesStore::merge(&db, series.id, series.id).await.is_err());
+        assert_eq!(EpisodeStore::for_series(&db, series.id).await.unwrap().len(), 2);
+        assert_eq!(SeriesStore::all(&db).await.unwrap(), [series]);
+    }
+
+    #[tokio::test]
+    async fn merge_carries_notes_and_reports_what_it_drops() {
+        let db = memory_db().await;
+        let target = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        let source = SeriesFixture::new("narutoo", "Naruto")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        let note = |episode: entity::episode::Model, notes: &str| {
+            let mut episode: entity::episode::ActiveModel = episode.into();
+            episode.notes = Set(Some(notes.to_string()));
+            episode
+        };
+        let target_episodes = EpisodeStore::for_series(&db, target.id).await.unwrap();
+        note(target_episodes[1].clone(), "kept").update(&db).await.unwrap();
+        for episode in EpisodeStore::for_series(&db, source.id).await.unwrap() {
+            note(episode, "from source").update(&db).await.unwrap();
+        }
+
+        let report = SeriesStore::merge(&db, source.id, target.id).await.unwrap().unwrap();
+
+        assert_eq!(report.collisions, 2);
+        assert_eq!(report.discarded, [2]);
+        let episodes = EpisodeStore::for_series(&db, target.id).await.unwrap();
+        assert_eq!(episodes[0].notes.as_deref(), Some("from source"));
+        assert!(!episodes[0].edited_by_user);
+        assert_eq!(episodes[1].notes.as_deref(), Some("kept"));
+    }
+
+    #[tokio::test]
+    async fn merged_slug_can_be_scraped_again() {
+        let db = memory_db().await;
+        let target = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=1, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        let source = SeriesFixture::new("narutoo", "Naruto")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        let old_links: Vec<_> = EpisodeStore::for_series(&db, source.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|ep| ep.permalink.unwrap())
+            .collect();
+
+        SeriesStore::merge(&db, source.id, target.id).await.unwrap().unwrap();
+        let merged = EpisodeStore::for_series(&db, target.id).await.unwrap();
+        assert_eq!(merged[1].permalink, Some(episode_permalink("naruto", 2)));
+        for (link, episode) in old_links.iter().zip(&merged) {
+            let found = EpisodeStore::find_by_permalink(&db, link).await.unwrap();
+            assert_eq!(found.map(|ep| ep.id), Some(episode.id));
+        }
+
+        let again = SeriesFixture::new("narutoo", "Naruto")
+            .episodes(1..=2, EpisodeType::Filler)
+            .insert(&db)
+            .await
+            .unwrap();
+        let rescraped = EpisodeStore::for_series(&db, again.id).await.unwrap();
+        assert_eq!(rescraped[0].permalink.as_ref(), Some(&old_links[0]));
+        let found = EpisodeStore::find_by_permalink(&db, &old_links[0]).await.unwrap();
+        assert_eq!(found.map(|ep| ep.id), Some(rescraped[0].id));
+
+        // Deleting the merged series takes its aliases along.
+        SeriesStore::delete(&db, target.id).await.unwrap().unwrap();
+        assert_eq!(PermalinkAlias::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn create_many_writes_episodes_in_order() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("naruto", "Naruto")
+            .episodes([3, 1, 2], EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let episodes = EpisodeStore::for_series(&db, series.id).await.unwrap();
+        let numbers: Vec<_> = episodes.iter().map(|ep| ep.episode_num).collect();
+        assert_eq!(numbers, [1, 2, 3]);
+        assert!(episodes.iter().all(|ep| ep.show_id == series.id));
+    }
+
+    #[tokio::test]
+    async fn create_many_updates_existing_numbers() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Unknown, "Romance Dawn")
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let episodes = [
+            NewEpisode {
+                number: 1,
+                episode_type: EpisodeType::Canon,
+                title: Some("I'm Luffy!".to_string()),
+            },
+            NewEpisode {
+                number: 2,
+                episode_type: EpisodeType::Canon,
+                title: None,
+            },
+        ];
+        EpisodeStore::create_many(&db, series.id, &episodes).await.unwrap();
+
+        let stored = EpisodeStore::for_series(&db, series.id).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].episode_type, entity::episode::EpisodeType::Canon);
+        assert_eq!(stored[0].title.as_deref(), Some("I'm Luffy!"));
+    }
+
+    #[tokio::test]
+    async fn create_many_keeps_user_edits() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Unknown, "Romance Dawn")
+            .insert(&db)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        let edit = EpisodeEdit {
+            title: Some("I'm Luffy!".to_string()),
+            override_type: Some(EpisodeType::Canon),
+            notes: Some("Retitled by hand".to_string()),
+        };
+        EpisodeStore::edit(&db, episode.id, edit).await.unwrap();
+
+        let rescrape = [NewEpisode {
+            number: 1,
+            episode_type: EpisodeType::Filler,
+            title: None,
+        }];
+        EpisodeStore::create_many(&db, series.id, &rescrape).await.unwrap();
+
+        let stored = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        assert!(stored.edited_by_user);
+        assert_eq!(stored.episode_type, entity::episode::EpisodeType::Filler);
+        assert_eq!(stored.override_type, Some(entity::episode::EpisodeType::Canon));
+        assert_eq!(stored.title.as_deref(), Some("I'm Luffy!"));
+        assert_eq!(stored.notes.as_deref(), Some("Retitled by hand"));
+
+        let data = EpisodeData::from(stored);
+        assert_eq!(data.episode_type, EpisodeType::Canon);
+        assert_eq!(data.overridden_from, Some(EpisodeType::Filler));
+    }
+
+    #[tokio::test]
+    async fn type_only_edits_leave_titles_to_the_scraper() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Unknown, "Romance Dawn")
+            .insert(&db)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        let edit = EpisodeEdit {
+            title: Some("Romance Dawn".to_string()),
+            override_type: Some(EpisodeType::Canon),
+            notes: None,
+        };
+        let stored = EpisodeStore::edit(&db, episode.id, edit).await.unwrap().unwrap();
+        assert!(!stored.edited_by_user);
+
+        let rescrape = [NewEpisode {
+            number: 1,
+            episode_type: EpisodeType::Canon,
+            title: Some("I'm Luffy!".to_string()),
+        }];
+        EpisodeStore::create_many(&db, series.id, &rescrape).await.unwrap();
+        let stored = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        assert_eq!(stored.title.as_deref(), Some("I'm Luffy!"));
+        assert_eq!(stored.override_type, Some(entity::episode::EpisodeType::Canon));
+    }
+
+    #[tokio::test]
+    async fn only_title_edits_mark_an_episode_as_edited() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Canon, "Romance Dawn")
+            .insert(&db)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        let edit = |title: Option<&str>, notes: &str| EpisodeEdit {
+            title: title.map(str::to_string),
+            override_type: None,
+            notes: Some(notes.to_string()),
+        };
+
+        let stored = EpisodeStore::edit(&db, episode.id, edit(None, "Watch this"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!stored.edited_by_user);
+
+        let stored = EpisodeStore::edit(&db, episode.id, edit(Some("I'm Luffy!"), "Watch this"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.edited_by_user);
+
+        let stored = EpisodeStore::edit(&db, episode.id, edit(None, ""))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.edited_by_user);
+        assert_eq!(stored.notes, None);
+
+        let stored = EpisodeStore::edit(&db, episode.id, edit(Some(" "), ""))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!stored.edited_by_user);
+        assert_eq!(stored.title, None);
+    }
+
+    #[tokio::test]
+    async fn permalinks_survive_delete_and_rescrape() {
+        let db = memory_db().await;
+        let fixture =
+            || SeriesFixture::new("one-piece", "One Piece").episodes(1..=2, EpisodeType::Canon);
+        let series = fixture().insert(&db).await.unwrap();
+        let before = EpisodeStore::for_series(&db, series.id).await.unwrap();
+        assert_eq!(
+            before[0].permalink.as_deref(),
+            Some(episode_permalink("one-piece", 1).as_str())
+        );
+
+        SeriesStore::delete(&db, series.id).await.unwrap();
+        fixture().insert(&db).await.unwrap();
+
+        let permalink = before[1].permalink.as_deref().unwrap();
+        let found = EpisodeStore::find_by_permalink(&db, permalink)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(found.id, before[1].id);
+        assert_eq!(found.episode_num, 2);
+    }
+
+    #[tokio::test]
+    async fn backfill_permalinks_fills_missing() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episodes(1..=3, EpisodeType::Canon)
+            .insert(&db)
+            .await
+            .unwrap();
+        Episode::update_many()
+            .col_expr(episode::Column::Permalink, Expr::value(None::<String>))
+            .exec(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(EpisodeStore::backfill_permalinks(&db).await.unwrap(), 3);
+        assert_eq!(EpisodeStore::backfill_permalinks(&db).await.unwrap(), 0);
+        let episodes = EpisodeStore::for_series(&db, series.id).await.unwrap();
+        assert_eq!(
+            episodes[2].permalink.as_deref(),
+            Some(episode_permalink("one-piece", 3).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn episode_list_filters_by_effective_type() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("bleach", "Bleach")
+            .episodes(1..=2, EpisodeType::Filler)
+            .insert(&db)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(0);
+        let edit = EpisodeEdit {
+            title: None,
+            override_type: Some(EpisodeType::Canon),
+            notes: None,
+        };
+        EpisodeStore::edit(&db, episode.id, edit).await.unwrap();
+
+        let query = EpisodeQuery {
+            episode_type: Some(EpisodeType::Filler),
+            ..Default::default()
+        };
+        let page = EpisodeStore::list(&db, &query).await.unwrap();
+
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].episode_num, 2);
+    }
+
+    #[tokio::test]
+    async fn create_many_accepts_empty_input() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("bleach", "Bleach").insert(&db).await.unwrap();
+
+        let written = EpisodeStore::create_many(&db, series.id, &[]).await.unwrap();
+
+        assert_eq!(written, 0);
+    }
+
+    #[tokio::test]
+    async fn episode_list_filters_by_series_and_type() {
+        let db = memory_db().await;
+        SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Canon, "Romance Dawn")
+            .episode(131, EpisodeType::Filler, "The First Patient")
+            .insert(&db)
+            .await
+            .unwrap();
+        SeriesFixture::new("naruto", "Naruto")
+            .episodes(26..=28, EpisodeType::Filler)
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let query = EpisodeQuery {
+            series: Some("one-piece".to_string()),
+            episode_type: Some(EpisodeType::Filler),
+            ..Default::default()
+        };
+        let page = EpisodeStore::list(&db, &query).await.unwrap();
+
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].episode_num, 131);
+    }
+
+    #[tokio::test]
+    async fn episode_list_for_unknown_series_is_empty() {
+        let db = memory_db().await;
+
+        let query = EpisodeQuery {
+            series: Some("missing".to_string()),
+            ..Default::default()
+        };
+        let page = EpisodeStore::list(&db, &query).await.unwrap();
+
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 0);
+    }
+
+    #[tokio::test]
+    async fn huge_pages_are_clamped_or_rejected() {
+        let db = memory_db().await;
+        SeriesFixture::new("one-piece", "One Piece")
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let page = PageRequest::from_params(Some(u64::MAX), Some(MAX_PER_PAGE), None).unwrap();
+        let query = SeriesQuery {
+            page,
+            ..Default::default()
+        };
+        let listed = SeriesStore::list(&db, &query).await.unwrap();
+        assert_eq!(listed.page, MAX_PAGE);
+        assert!(listed.items.is_empty());
+
+        let cursor = format!("p{}", u64::MAX);
+        assert!(PageRequest::from_params(None, None, Some(&cursor)).is_err());
+    }
+
+    #[tokio::test]
+    async fn series_list_paginates_and_searches() {
+        let db = memory_db().await;
+        for (slug, title) in [("a", "Alpha"), ("b", "Beta"), ("c", "Gamma")] {
+            SeriesFixture::new(slug, title).insert(&db).await.unwrap();
+        }
+
+        let query = SeriesQuery {
+            page: PageRequest::new(Some(2), Some(2)),
+            sort: Some(Sort {
+                field: SeriesSortField::Title,
+                descending: true,
+            }),
+            q: None,
+        };
+        let page = SeriesStore::list(&db, &query).await.unwrap();
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.items[0].title, "Alpha");
+        assert_eq!(page.next_cursor, None);
+
+        let query = SeriesQuery {
+            q: Some("amm".to_string()),
+            ..Default::default()
+        };
+        let page = SeriesStore::list(&db, &query).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].slug, "c");
+    }
+
+    #[tokio::test]
+    async fn search_ignores_case() {
+        let db = memory_db().await;
+        SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Canon, "Romance Dawn")
+            .insert(&db)
+            .await
+            .unwrap();
+
+        let series = SeriesQuery {
+            q: Some("PIECE".to_string()),
+            ..Default::default()
+        };
+        let episodes = EpisodeQuery {
+            q: Some("romance DAWN".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(SeriesStore::list(&db, &series).await.unwrap().total, 1);
+        assert_eq!(EpisodeStore::list(&db, &episodes).await.unwrap().total, 1);
+    }
+
+    #[tokio::test]
+    async fn search_folds_only_ascii() {
+        let db = memory_db().await;
+        SeriesFixture::new("pokemon", "POKÉMON")
+            .insert(&db)
+            .await
+            .unwrap();
+        let search = |q: &str| SeriesQuery {
+            q: Some(q.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(SeriesStore::list(&db, &search("pokÉmon")).await.unwrap().total, 1);
+        assert_eq!(SeriesStore::list(&db, &search("pokémon")).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn search_treats_wildcards_literally() {
+        let db = memory_db().await;
+        for (slug, title) in [("a", "100% Pascal"), ("b", "1000 Pascals"), ("c", "Snake_Case")] {
+            SeriesFixture::new(slug, title).insert(&db).await.unwrap();
+        }
+
+        let search = |q: &str| SeriesQuery {
+            q: Some(q.to_string()),
+            ..Default::default()
+        };
+        let page = SeriesStore::list(&db, &search("100%")).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].slug, "a");
+        let page = SeriesStore::list(&db, &search("e_c")).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].slug, "c");
+        assert_eq!(SeriesStore::list(&db, &search("0_P")).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn count_by_type_uses_effective_type() {
+        let db = memory_db().await;
+        let series = SeriesFixture::new("bleach", "Bleach")
+            .episodes(1..=3, EpisodeType::Canon)
+            .episodes(4..=5, EpisodeType::Filler)
+            .insert(&db)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&db, series.id).await.unwrap().remove(3);
+        let edit = EpisodeEdit {
+            title: None,
+            override_type: Some(EpisodeType::AnimeCanon),
+            notes: None,
+        };
+        EpisodeStore::edit(&db, episode.id, edit).await.unwrap();
+
+        let counts = EpisodeStore::count_by_type(&db).await.unwrap();
+
+        assert_eq!(
+            counts,
+            vec![
+                (EpisodeType::Canon, 3),
+                (EpisodeType::MixedCanon, 0),
+                (EpisodeType::Filler, 1),
+                (EpisodeType::AnimeCanon, 1),
+                (EpisodeType::Unknown, 0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_and_recently_fetched() {
+        let db = memory_db().await;
+        let now = Utc::now();
+        for (slug, fetched) in [
+            ("fresh", Some(now)),
+            ("old", Some(now - chrono::Duration::days(30))),
+            ("never", None),
+        ] {
+            let series = SeriesFixture::new(slug, slug).insert(&db).await.unwrap();
+            let mut series: series::ActiveModel = series.into();
+            series.last_fetched = Set(fetched.map(|at| at.with_timezone(&Local)));
+            series.update(&db).await.unwrap();
+        }
+
+        let cutoff = now - chrono::Duration::days(7);
+        let stale = SeriesStore::stale(&db, cutoff, PageRequest::new(Some(1), Some(10)))
+            .await
+            .unwrap();
+        let mut slugs: Vec<_> = stale.items.iter().map(|s| s.slug.as_str()).collect();
+        slugs.sort_unstable();
+        assert_eq!(slugs, ["never", "old"]);
+
+        let recent = SeriesStore::recently_fetched(&db, 5).await.unwrap();
+        let slugs: Vec<_> = recent.iter().map(|s| s.slug.as_str()).collect();
+        assert_eq!(slugs, ["fresh", "old"]);
+        assert_eq!(SeriesStore::count(&db).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn import_all_round_trips_an_export() {
+        let source = memory_db().await;
+        let series = SeriesFixture::new("one-piece", "One Piece")
+            .episode(1, EpisodeType::Canon, "Romance Dawn")
+            .episode(2, EpisodeType::Filler, "Enter the Great Swordsman")
+            .insert(&source)
+            .await
+            .unwrap();
+        SeriesFixture::new("bleach", "Bleach")
+            .episodes(1..=3, EpisodeType::Canon)
+            .insert(&source)
+            .await
+            .unwrap();
+        let episode = EpisodeStore::for_series(&source, series.id).await.unwrap().remove(1);
+        let edit = EpisodeEdit {
+            title: Some("I'm Zoro".to_string()),
+            override_type: Some(EpisodeType::Canon),
+            notes: Some("Not filler".to_string()),
+        };
+        EpisodeStore::edit(&source, episode.id, edit).await.unwrap();
+        let exported = SeriesStore::export_all(&source).await.unwrap();
+
+        let target = memory_db().await;
+        SeriesFixture::new("one-piece", "Old title")
+            .episodes(1..=5, EpisodeType::Unknown)
+            .insert(&target)
+            .await
+            .unwrap();
+        let report = SeriesStore::import_all(&target, &exported).await.unwrap();
+
+        assert_eq!(report, ImportReport { series: 2, episodes: 5 });
+        let restored = SeriesStore::export_all(&target).await.unwrap();
+        let strip_ids = |exports: Vec<SeriesExport>| {
+            exports
+                .into_iter()
+                .map(|export| {
+                    let episodes: Vec<_> = export
+                        .episodes
+                        .into_iter()
+                        .map(|ep| EpisodeData {
+                            id: Uuid::nil(),
+                            series_id: Uuid::nil(),
+                            ..ep
+                        })
+                        .collect();
+                    (export.series.slug, export.series.title, episodes)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(strip_ids(restored.series), strip_ids(exported.series));
+    }
+
+    #[tokio::test]
+    async fn import_all_restores_merge_aliases() {
+        let source = memory_db().await;
+        let target = SeriesFixture::new("naruto", "Naruto")
+            .episodes(1..=1, EpisodeType::Canon)
+            .insert(&source)
+            .await
+            .unwrap();
+        let merged = SeriesFixture::new("narutoo", "Naruto")
+            .episodes(1..=2, EpisodeType::Canon)
+            .insert(&source)
+            .await
+            .unwrap();
+        SeriesStore::merge(&source, merged.id, target.id).await.unwrap();
+        let exported = SeriesStore::export_all(&source).await.unwrap();
+        assert_eq!(exported.aliases.len(), 2);
+
+        let restored = memory_db().await;
+        SeriesStore::import_all(&restored, &exported).await.unwrap();
+
+        for alias in &exported.aliases {
+            let episode = EpisodeStore::find_by_permalink(&restored, &alias.permalink)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(episode.permalink.as_ref(), Some(&alias.episode_permalink));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! In-memory SQLite databases and fixtures for store tests.
+
+use entity::series;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use super::{connect, sync_schema, EpisodeStore, NewEpisode, SeriesStore};
+use crate::types::EpisodeType;
+
+/// A fresh, empty database with the full schema applied.
+pub async fn memory_db() -> DatabaseConnection {
+    let db = connect("sqlite::memory:")
+        .await
+        .expect("in-memory SQLite should connect");
+    sync_schema(&db).await.expect("schema sync should succeed");
+    db
+}
+
+/// Builder for a series and its episodes.
+pub struct SeriesFixture {
+    slug: String,
+    title: String,
+    episodes: Vec<NewEpisode>,
+}
+
+impl SeriesFixture {
+    pub fn new(slug: &str, title: &str) -> Self {
+        Self {
+            slug: slug.to_string(),
+            title: title.to_string(),
+            episodes: Vec::new(),
+        }
+    }
+
+    pub fn episode(mut self, number: i32, episode_type: EpisodeType, title: &str) -> Self {
+        self.episodes.push(NewEpisode {
+            number,
+            episode_type,
+            title: Some(title.to_string()),
+        });
+        self
+    }
+
+    /// Adds untitled episodes `numbers`, all of `episode_type`.
+    pub fn episodes(
+        mut self,
+        numbers: impl IntoIterator<Item = i32>,
+        episode_type: EpisodeType,
+    ) -> Self {
+        self.episodes.extend(numbers.into_iter().map(|number| NewEpisode {
+            number,
+            episode_type,
+            title: None,
+        }));
+        self
+    }
+
+    pub async fn insert(self, db: &DatabaseConnection) -> Result<series::Model, DbErr> {
+        let series = SeriesStore::find_or_create(db, &self.slug, &self.title).await?;
+        EpisodeStore::create_many(db, series.id, &self.episodes).await?;
+        Ok(series)
+    }
+}
@@ -0,0 +1,573 @@
+//! Export formats for series and the whole library.
+
+use crate::stats::{filler_ranges, EpisodeRange};
+use crate::types::{EpisodeData, EpisodeType, SeriesData};
+use crate::validation::validate_slug;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// Bumped whenever the shape of the export files changes.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Serialization formats the REST list endpoints answer in, picked with
+/// `?format=`, and the tabs of the home page's Output view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Ron,
+    Yaml,
+    Toml,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Json,
+        ExportFormat::Ron,
+        ExportFormat::Yaml,
+        ExportFormat::Toml,
+    ];
+
+    /// The name accepted in `?format=`, also used as the file extension.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Ron => "ron",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Toml => "toml",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Ron => "application/ron",
+            ExportFormat::Yaml => "application/yaml",
+            ExportFormat::Toml => "application/toml",
+        }
+    }
+
+    /// Label shown on the Output tab.
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Ron => "RON",
+            ExportFormat::Yaml => "YAML",
+            ExportFormat::Toml => "TOML",
+        }
+    }
+
+    /// Serializes `value` pretty-printed in this format.
+    pub fn render<T: Serialize>(self, value: &T) -> Result<String, String> {
+        match self {
+            ExportFormat::Json => serde_json::to_string_pretty(value).map_err(|err| err.to_string()),
+            ExportFormat::Ron => {
+                let config = ron::ser::PrettyConfig::new().struct_names(true);
+                ron::ser::to_string_pretty(value, config).map_err(|err| err.to_string())
+            }
+            ExportFormat::Yaml => serde_yaml::to_string(value).map_err(|err| err.to_string()),
+            ExportFormat::Toml => toml::to_string(value).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("yml") {
+            return Ok(ExportFormat::Yaml);
+        }
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown format `{s}`; expected json, ron, yaml or toml"))
+    }
+}
+
+/// One series and its episodes, as shown on the series page and written to
+/// `series/<slug>.json` in a library archive.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SeriesExport {
+    pub series: SeriesData,
+    pub episodes: Vec<EpisodeData>,
+}
+
+/// An old permalink kept alive by a series merge, and the permalink of the
+/// episode it now resolves to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PermalinkAliasExport {
+    pub permalink: String,
+    pub episode_permalink: String,
+}
+
+/// The whole database as one JSON document, for backups and for moving a
+/// curated dataset to another instance. Episodes carry their overrides and
+/// manual edits.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DatabaseSnapshot {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub generated_at: DateTime<Utc>,
+    pub series: Vec<SeriesExport>,
+    /// Missing from snapshots taken before aliases were exported.
+    #[serde(default)]
+    pub aliases: Vec<PermalinkAliasExport>,
+}
+
+impl DatabaseSnapshot {
+    pub fn new(series: Vec<SeriesExport>, aliases: Vec<PermalinkAliasExport>) -> Self {
+        Self {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: Utc::now(),
+            series,
+            aliases,
+        }
+    }
+
+    /// Rejects snapshots this version cannot restore faithfully.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.schema_version > EXPORT_SCHEMA_VERSION {
+            return Err(format!(
+                "snapshot schema version {} is newer than the supported {EXPORT_SCHEMA_VERSION}",
+                self.schema_version
+            ));
+        }
+        let mut slugs = HashSet::new();
+        let mut permalinks = HashSet::new();
+        for export in &self.series {
+            validate_slug(&export.series.slug)?;
+            if !slugs.insert(export.series.slug.as_str()) {
+                return Err(format!("series `{}` appears twice", export.series.slug));
+            }
+            let mut numbers = HashSet::new();
+            if let Some(episode) = export.episodes.iter().find(|ep| !numbers.insert(ep.number)) {
+                return Err(format!(
+                    "series `{}` has episode {} twice",
+                    export.series.slug, episode.number
+                ));
+            }
+            // Blank permalinks are derived on import and cannot collide.
+            let duplicate = export
+                .episodes
+                .iter()
+                .filter(|ep| !ep.permalink.is_empty())
+                .find(|ep| !permalinks.insert(ep.permalink.as_str()));
+            if let Some(episode) = duplicate {
+                return Err(format!("permalink `{}` appears twice", episode.permalink));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `manifest.json` at the root of a library archive.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryManifest {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub generated_at: DateTime<Utc>,
+    pub series: Vec<ManifestEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub slug: String,
+    pub title: String,
+    pub file: String,
+    pub episodes: usize,
+}
+
+/// Number of episodes of each type, in `EpisodeType::ALL` order.
+pub fn type_counts(episodes: &[EpisodeData]) -> [(EpisodeType, usize); EpisodeType::ALL.len()] {
+    EpisodeType::ALL.map(|ty| {
+        let count = episodes.iter().filter(|ep| ep.episode_type == ty).count();
+        (ty, count)
+    })
+}
+
+/// A series' filler episodes as compact ranges, the format community skip
+/// scripts take.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct FillerRanges {
+    pub ranges: Vec<EpisodeRange>,
+    /// The ranges joined as `26-32, 50, 90-110`; empty without filler.
+    pub text: String,
+}
+
+impl FillerRanges {
+    pub fn from_episodes(episodes: &[EpisodeData]) -> Self {
+        let ranges = filler_ranges(episodes);
+        let text = ranges
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self { ranges, text }
+    }
+}
+
+/// Quotes a value for a YAML double-quoted scalar, escaping line breaks and
+/// other control characters so it stays on one line.
+fn yaml_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\x{:02X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Escapes characters that would break a Markdown table cell. Line breaks
+/// become `<br>` and other control characters spaces, keeping the row on
+/// one line.
+fn table_cell(value: &str) -> String {
+    value
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+        .replace(|c: char| c.is_control(), " ")
+        .replace('|', "\\|")
+}
+
+/// Escapes inline Markdown syntax in free text such as a title, for use in
+/// headings and list items. Line breaks and other control characters become
+/// spaces, keeping the text on its line.
+fn markdown_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders a series as a Markdown note with YAML frontmatter, for knowledge
+/// base tools like Obsidian or Notion.
+pub fn markdown_note(series: &SeriesData, episodes: &[EpisodeData]) -> String {
+    let mut lines = vec![
+        "---".to_string(),
+        format!("id: {}", series.id),
+        format!("slug: {}", yaml_string(&series.slug)),
+        format!("title: {}", yaml_string(&series.title)),
+        format!("episodes: {}", episodes.len()),
+    ];
+    for (ty, count) in type_counts(episodes) {
+        lines.push(format!("{}: {count}", ty.as_str()));
+    }
+    lines.push(match series.last_fetched {
+        Some(at) => format!("last_fetched: {}", at.to_rfc3339()),
+        None => "last_fetched: null".to_string(),
+    });
+    lines.push("tags: [anime, seiten]".to_string());
+    lines.push("---".to_string());
+    lines.push(String::new());
+
+    lines.push(format!("# {}", markdown_text(&series.title)));
+    lines.push(String::new());
+    lines.push("| # | Title | Type | Permalink | Notes |".to_string());
+    lines.push("|---|-------|------|-----------|-------|".to_string());
+    for episode in episodes {
+        lines.push(format!(
+            "| {} | {} | {} | `{}` | {} |",
+            episode.number,
+            table_cell(episode.title.as_deref().unwrap_or("")),
+            episode.episode_type.label(),
+            episode.permalink,
+            table_cell(episode.notes.as_deref().unwrap_or("")),
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Coloured marker for a classification, matching the badge colours on the
+/// series page.
+fn type_emoji(ty: EpisodeType) -> &'static str {
+    match ty {
+        EpisodeType::Canon => "🟢",
+        EpisodeType::MixedCanon => "🔵",
+        EpisodeType::Filler => "🔴",
+        EpisodeType::AnimeCanon => "🟠",
+        EpisodeType::Unknown => "⚪",
+    }
+}
+
+/// Renders a series as a Markdown episode guide meant for people: a summary,
+/// the episode table with type markers, a checklist of the episodes to watch
+/// (canon and mixed canon) and an optional one for anime canon, which is
+/// neither the manga's story nor filler. Pastes cleanly into Discord, Reddit
+/// or Notion.
+pub fn episode_guide(series: &SeriesData, episodes: &[EpisodeData]) -> String {
+    let mut lines = vec![
+        format!("# {} — Episode Guide", markdown_text(&series.title)),
+        String::new(),
+    ];
+
+    let mut summary = vec![format!("**{} episodes**", episodes.len())];
+    summary.extend(
+        type_counts(episodes)
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(ty, count)| format!("{} {count} {}", type_emoji(ty), ty.label())),
+    );
+    lines.push(summary.join(" · "));
+    let filler = FillerRanges::from_episodes(episodes);
+    if !filler.text.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("**Skip:** {}", filler.text));
+    }
+    lines.push(String::new());
+
+    lines.push("| # | Title | Type |".to_string());
+    lines.push("|--:|-------|------|".to_string());
+    for episode in episodes {
+        lines.push(format!(
+            "| {} | {} | {} {} |",
+            episode.number,
+            table_cell(episode.title.as_deref().unwrap_or("")),
+            type_emoji(episode.episode_type),
+            episode.episode_type.label(),
+        ));
+    }
+    lines.push(String::new());
+
+    lines.push("## Canon checklist".to_string());
+    lines.push(String::new());
+    let to_watch = episodes.iter().filter(|ep| {
+        matches!(
+            ep.episode_type,
+            EpisodeType::Canon | EpisodeType::MixedCanon
+        )
+    });
+    lines.extend(to_watch.map(checklist_item));
+
+    let anime_canon: Vec<_> = episodes
+        .iter()
+        .filter(|ep| ep.episode_type == EpisodeType::AnimeCanon)
+        .map(checklist_item)
+        .collect();
+    if !anime_canon.is_empty() {
+        lines.push(String::new());
+        lines.push("## Anime canon (optional)".to_string());
+        lines.push(String::new());
+        lines.extend(anime_canon);
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn checklist_item(episode: &EpisodeData) -> String {
+    match episode.title.as_deref() {
+        Some(title) => format!("- [ ] {}. {}", episode.number, markdown_text(title)),
+        None => format!("- [ ] {}", episode.number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Page;
+    use uuid::Uuid;
+
+    fn series() -> SeriesData {
+        SeriesData {
+            id: Uuid::nil(),
+            slug: "one-piece".to_string(),
+            title: "One Piece".to_string(),
+            last_fetched: None,
+        }
+    }
+
+    fn episode(number: i32, episode_type: EpisodeType, title: Option<&str>) -> EpisodeData {
+        EpisodeData {
+            id: Uuid::nil(),
+            permalink: format!("p{number}"),
+            series_id: Uuid::nil(),
+            number,
+            episode_type,
+            overridden_from: None,
+            title: title.map(str::to_string),
+            notes: None,
+            edited_by_user: false,
+        }
+    }
+
+    #[test]
+    fn every_format_renders_a_page() {
+        let page = Page::new(vec![episode(1, EpisodeType::Canon, None)], 1, 50, 1);
+        for format in ExportFormat::ALL {
+            let body = format.render(&page).unwrap();
+            assert!(body.contains("Canon"), "{format}: {body}");
+            assert_eq!(format.as_str().parse::<ExportFormat>(), Ok(format));
+        }
+    }
+
+    #[test]
+    fn snapshot_rejects_duplicate_permalinks() {
+        let export = |slug: &str| SeriesExport {
+            series: SeriesData {
+                slug: slug.to_string(),
+                ..series()
+            },
+            episodes: vec![episode(1, EpisodeType::Canon, None)],
+        };
+        let snapshot = DatabaseSnapshot::new(vec![export("one-piece")], Vec::new());
+        assert_eq!(snapshot.validate(), Ok(()));
+
+        let both = vec![export("one-piece"), export("bleach")];
+        let snapshot = DatabaseSnapshot::new(both, Vec::new());
+        assert_eq!(
+            snapshot.validate(),
+            Err("permalink `p1` appears twice".to_string())
+        );
+    }
+
+    #[test]
+    fn yaml_string_escapes_quotes_and_backslashes() {
+        assert_eq!(yaml_string("plain"), r#""plain""#);
+        assert_eq!(yaml_string(r#"Say "hi" \ bye"#), r#""Say \"hi\" \\ bye""#);
+    }
+
+    #[test]
+    fn yaml_string_escapes_control_characters() {
+        assert_eq!(yaml_string("a\nb\r\tc\u{7}"), r#""a\nb\r\tc\x07""#);
+    }
+
+    #[test]
+    fn table_cell_escapes_pipes() {
+        assert_eq!(table_cell("Luffy | Zoro"), r"Luffy \| Zoro");
+    }
+
+    #[test]
+    fn markdown_note_uses_readable_type_labels() {
+        let episodes = [
+            episode(1, EpisodeType::MixedCanon, Some("A | B")),
+            episode(2, EpisodeType::AnimeCanon, None),
+        ];
+        let note = markdown_note(&series(), &episodes);
+
+        assert!(note.starts_with("---\n"));
+        assert!(note.contains("slug: \"one-piece\"\n"));
+        assert!(note.contains("mixed: 1\n"));
+        assert!(note.contains("| 1 | A \\| B | Mixed Canon | `p1` |  |\n"));
+        assert!(note.contains("| 2 |  | Anime Canon | `p2` |  |\n"));
+        assert!(!note.contains("MixedCanon"));
+    }
+
+    #[test]
+    fn markdown_note_keeps_multi_line_text_on_one_line() {
+        let series = SeriesData {
+            title: "One\nPiece".to_string(),
+            ..series()
+        };
+        let mut noted = episode(1, EpisodeType::Filler, Some("Part 1\r\nPart 2"));
+        noted.notes = Some("Skippable.\nRecap of\tarc 1.".to_string());
+        let note = markdown_note(&series, &[noted]);
+
+        assert!(note.contains("title: \"One\\nPiece\"\n"));
+        assert!(note.contains("| 1 | Part 1<br>Part 2 | Filler | `p1` | Skippable.<br>Recap of arc 1. |\n"));
+    }
+
+    #[test]
+    fn filler_ranges_collapse_consecutive_episodes() {
+        let episodes = [
+            episode(5, EpisodeType::Filler, None),
+            episode(3, EpisodeType::Filler, None),
+            episode(4, EpisodeType::Filler, None),
+            episode(6, EpisodeType::Canon, None),
+            episode(8, EpisodeType::Filler, None),
+        ];
+        let filler = FillerRanges::from_episodes(&episodes);
+
+        assert_eq!(filler.text, "3-5, 8");
+        assert_eq!(
+            filler.ranges,
+            [
+                EpisodeRange { start: 3, end: 5 },
+                EpisodeRange { start: 8, end: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn filler_ranges_of_no_filler_are_empty() {
+        let empty = FillerRanges::from_episodes(&[]);
+        assert!(empty.ranges.is_empty());
+        assert_eq!(empty.text, "");
+
+        let canon_only = FillerRanges::from_episodes(&[episode(1, EpisodeType::Canon, None)]);
+        assert_eq!(canon_only.text, "");
+    }
+
+    #[test]
+    fn episode_guide_lists_filler_and_a_canon_checklist() {
+        let episodes = [
+            episode(1, EpisodeType::Canon, Some("Romance Dawn")),
+            episode(2, EpisodeType::MixedCanon, None),
+            episode(3, EpisodeType::Filler, Some("Detour")),
+            episode(4, EpisodeType::AnimeCanon, Some("Side Story")),
+        ];
+        let guide = episode_guide(&series(), &episodes);
+
+        assert!(guide.starts_with("# One Piece — Episode Guide\n"));
+        assert!(guide.contains("**4 episodes** · 🟢 1 Canon · 🔵 1 Mixed Canon"));
+        assert!(guide.contains("\n**Skip:** 3\n"));
+        assert!(guide.contains("| 3 | Detour | 🔴 Filler |\n"));
+
+        let checklist = guide.split("## Canon checklist").nth(1).unwrap();
+        let (canon, anime_canon) = checklist.split_once("## Anime canon (optional)").unwrap();
+        assert!(canon.contains("- [ ] 1. Romance Dawn\n"));
+        assert!(canon.contains("- [ ] 2\n"));
+        assert!(!canon.contains("Detour"));
+        assert!(!canon.contains("Side Story"));
+        assert_eq!(anime_canon, "\n\n- [ ] 4. Side Story\n");
+    }
+
+    #[test]
+    fn episode_guide_escapes_titles() {
+        let series = SeriesData {
+            title: "One\n# Piece".to_string(),
+            ..series()
+        };
+        let episodes = [episode(1, EpisodeType::Canon, Some("*Romance*\nDawn"))];
+        let guide = episode_guide(&series, &episodes);
+
+        assert!(guide.starts_with("# One \\# Piece — Episode Guide\n"));
+        assert!(guide.contains("- [ ] 1. \\*Romance\\* Dawn\n"));
+    }
+
+    #[test]
+    fn episode_guide_without_filler_has_no_skip_line() {
+        let guide = episode_guide(&series(), &[episode(1, EpisodeType::Canon, None)]);
+
+        assert!(!guide.contains("**Skip:**"));
+        assert!(guide.ends_with("- [ ] 1\n"));
+    }
+}
@@ -1,11 +1,49 @@
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
-    components::{Route, Router, Routes},
-    StaticSegment,
+    components::{Route, Router, Routes, A},
+    ParamSegment, StaticSegment,
 };
 
+#[cfg(feature = "ssr")]
+pub mod db;
+pub mod admin;
+pub mod api;
+pub mod config;
+pub mod dashboard;
+pub mod export;
+pub mod library;
+pub mod pager;
+pub mod series;
+pub mod stats;
+pub mod timeline;
+pub mod toast;
+pub mod types;
+pub mod validation;
+
+use export::ExportFormat;
+use serde::Serialize;
+use toast::{provide_toasts, use_toasts, Toaster, Toasts};
+use types::EpisodeType;
+use validation::{parse_scrape_input, ScrapeSource};
+
+/// Response header used to hand the per-request CSP nonce to the server's
+/// security middleware, which strips it before the response is sent.
+pub const NONCE_HEADER: &str = "x-seiten-nonce";
+
+/// Path prefix the app is served under behind a reverse proxy (e.g.
+/// `/seiten`), empty when served from the root. Read from `SEITEN_BASE_PATH`
+/// at build time so the server and the WASM bundle always agree.
+pub fn base_path() -> &'static str {
+    option_env!("SEITEN_BASE_PATH")
+        .unwrap_or_default()
+        .trim_end_matches('/')
+}
+
 pub fn shell(options: LeptosOptions) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    expose_nonce();
+
     view! {
         <!DOCTYPE html>
         <html lang="en" data-theme="mytheme">
@@ -13,7 +51,7 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                 <meta charset="utf-8"/>
                 <meta name="viewport" content="width=device-width, initial-scale=1"/>
                 <AutoReload options=options.clone()/>
-                <HydrationScripts options/>
+                <HydrationScripts options root=base_path()/>
                 <MetaTags/>
             </head>
             <body>
@@ -23,40 +61,134 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
     }
 }
 
+#[cfg(feature = "ssr")]
+fn expose_nonce() {
+    use http::{HeaderName, HeaderValue};
+    use leptos::nonce::use_nonce;
+
+    let Some(response) = use_context::<leptos_axum::ResponseOptions>() else {
+        return;
+    };
+    if let Some(value) = use_nonce().and_then(|nonce| HeaderValue::from_str(&nonce).ok()) {
+        response.insert_header(HeaderName::from_static(NONCE_HEADER), value);
+    }
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    provide_toasts();
 
     view! {
-        <Stylesheet id="leptos" href="/pkg/seiten.css"/>
+        <Stylesheet id="leptos" href=format!("{}/pkg/seiten.css", base_path())/>
 
         // sets the document title
         <Title text="Seiten - Anime Canon Manager"/>
 
         // content for this welcome page
-        <Router>
+        <Router base=base_path()>
+            <nav class="navbar bg-base-100 shadow gap-2">
+                <A href="/" attr:class="btn btn-ghost text-xl">"正典"</A>
+                <A href="/dashboard" attr:class="btn btn-ghost">"Dashboard"</A>
+                <A href="/library" attr:class="btn btn-ghost">"Library"</A>
+                <A href="/admin" attr:class="btn btn-ghost">"Admin"</A>
+            </nav>
             <main>
                 <Routes fallback=|| "Page not found.".into_view()>
                     <Route path=StaticSegment("") view=HomePage/>
+                    <Route path=StaticSegment("dashboard") view=dashboard::DashboardPage/>
+                    <Route path=StaticSegment("library") view=library::LibraryPage/>
+                    <Route path=StaticSegment("admin") view=admin::AdminPage/>
+                    <Route
+                        path=(StaticSegment("series"), ParamSegment("slug"))
+                        view=series::SeriesPage
+                    />
                 </Routes>
             </main>
         </Router>
+        <Toaster/>
+    }
+}
+
+/// Sends the input to the orchestration matching its detected source,
+/// reporting the outcome as a toast. Neither source can be fetched yet, so
+/// the toast only says so; a retry would fail the same way.
+fn scrape(input: RwSignal<String>, toasts: Toasts) {
+    let source = match parse_scrape_input(&input.get_untracked()) {
+        Ok(source) => source,
+        Err(message) => {
+            toasts.error(message);
+            return;
+        }
+    };
+
+    leptos::logging::log!("Scrape requested for {source}");
+    let reason = match source {
+        ScrapeSource::AnimeFillerList { .. } => "scraping is not available yet",
+        ScrapeSource::AniDb { .. } => "AniDB enrichment is not available yet",
+    };
+    toasts.info(format!("Cannot fetch {source}: {reason}."));
+}
+
+/// Placeholder series shown in the Output view until scraping can fill it.
+#[derive(Serialize)]
+struct Series {
+    title: &'static str,
+    slug: &'static str,
+    episodes: Vec<Episode>,
+}
+
+#[derive(Serialize)]
+struct Episode {
+    number: i32,
+    episode_type: EpisodeType,
+    title: Option<&'static str>,
+}
+
+fn sample_series() -> Series {
+    let episode = |number, episode_type, title| Episode {
+        number,
+        episode_type,
+        title: Some(title),
+    };
+    Series {
+        title: "One Piece",
+        slug: "one-piece",
+        episodes: vec![
+            episode(
+                1,
+                EpisodeType::Canon,
+                "I'm Luffy! The Man Who's Gonna Be King of the Pirates!",
+            ),
+            episode(2, EpisodeType::Canon, "Enter the Great Swordsman!"),
+            episode(
+                131,
+                EpisodeType::Filler,
+                "The First Patient! The Untold Story of the Rumble Ball!",
+            ),
+        ],
     }
 }
 
 /// Renders the home page of your application.
 #[component]
 fn HomePage() -> impl IntoView {
+    let sample = sample_series();
     let input_value = RwSignal::new(String::new());
     let count = RwSignal::new(0);
+    let toasts = use_toasts();
+    // Empty input is not an error until the user submits.
+    let detected = Memo::new(move |_| {
+        let value = input_value.get();
+        (!value.trim().is_empty()).then(|| parse_scrape_input(&value))
+    });
 
-    let on_scrape = move |_| {
-        leptos::logging::log!("Scrape clicked with value: {}", input_value.get());
-    };
+    let on_scrape = move |_| scrape(input_value, toasts);
 
     let on_sync = move |_| {
         leptos::logging::log!("Sync clicked");
+        toasts.info("Nothing to sync yet.");
     };
 
     let on_count_click = move |_| *count.write() += 1;
@@ -70,17 +202,34 @@ fn HomePage() -> impl IntoView {
 
                         <div class="form-control w-full">
                             <label class="label">
-                                <span class="label-text">"Anime Series URL"</span>
+                                <span class="label-text">"Anime Series"</span>
                             </label>
                             <input
                                 type="text"
                                 placeholder="https://www.animefillerlist.com/shows/one-piece"
-                                class="input input-bordered input-primary w-full"
+                                class="input input-bordered w-full"
+                                class=("input-primary", move || !matches!(detected.get(), Some(Err(_))))
+                                class=("input-error", move || matches!(detected.get(), Some(Err(_))))
                                 on:input=move |ev| {
                                     input_value.set(event_target_value(&ev));
                                 }
                                 prop:value=move || input_value.get()
                             />
+                            <label class="label">
+                                {move || match detected.get() {
+                                    Some(Ok(source)) => view! {
+                                        <span class="label-text-alt text-success">{format!("Detected {source}")}</span>
+                                    }.into_any(),
+                                    Some(Err(message)) => view! {
+                                        <span class="label-text-alt text-error">{message}</span>
+                                    }.into_any(),
+                                    None => view! {
+                                        <span class="label-text-alt opacity-70">
+                                            "animefillerlist.com show URL, show name or slug, or AniDB anime URL"
+                                        </span>
+                                    }.into_any(),
+                                }}
+                            </label>
                         </div>
 
                         <div class="card-actions justify-end mt-6 gap-3">
@@ -99,61 +248,25 @@ fn HomePage() -> impl IntoView {
                         <h2 class="card-title text-sm opacity-70">"Output"</h2>
 
                         <div role="tablist" class="tabs tabs-bordered">
-                            <input type="radio" name="output_tabs" role="tab" class="tab" aria-label="JSON" checked=true/>
-                            <div role="tabpanel" class="tab-content p-4 overflow-hidden">
-                                <pre class="bg-base-200 p-4 rounded-lg overflow-x-auto text-sm">
-{r#"{
-  "series": {
-    "title": "One Piece",
-    "slug": "one-piece",
-    "episodes": [
-      {
-        "number": 1,
-        "type": "Canon",
-        "title": "I'm Luffy! The Man Who's Gonna Be King of the Pirates!"
-      },
-      {
-        "number": 2,
-        "type": "Canon",
-        "title": "Enter the Great Swordsman!"
-      },
-      {
-        "number": 131,
-        "type": "Filler",
-        "title": "The First Patient! The Untold Story of the Rumble Ball!"
-      }
-    ]
-  }
-}"#}
-                                </pre>
-                            </div>
-
-                            <input type="radio" name="output_tabs" role="tab" class="tab" aria-label="RON"/>
-                            <div role="tabpanel" class="tab-content p-4 overflow-hidden">
-                                <pre class="bg-base-200 p-4 rounded-lg overflow-x-auto text-sm">
-{r#"Series(
-  title: "One Piece",
-  slug: "one-piece",
-  episodes: [
-    Episode(
-      number: 1,
-      episode_type: Canon,
-      title: Some("I'm Luffy! The Man Who's Gonna Be King of the Pirates!"),
-    ),
-    Episode(
-      number: 2,
-      episode_type: Canon,
-      title: Some("Enter the Great Swordsman!"),
-    ),
-    Episode(
-      number: 131,
-      episode_type: Filler,
-      title: Some("The First Patient! The Untold Story of the Rumble Ball!"),
-    ),
-  ],
-)"#}
-                                </pre>
-                            </div>
+                            {ExportFormat::ALL
+                                .into_iter()
+                                .map(|format| {
+                                    let body = format.render(&sample).unwrap_or_else(|err| err);
+                                    view! {
+                                        <input
+                                            type="radio"
+                                            name="output_tabs"
+                                            role="tab"
+                                            class="tab"
+                                            aria-label=format.label()
+                                            checked=format == ExportFormat::default()
+                                        />
+                                        <div role="tabpanel" class="tab-content p-4 overflow-hidden">
+                                            <pre class="bg-base-200 p-4 rounded-lg overflow-x-auto text-sm">{body}</pre>
+                                        </div>
+                                    }
+                                })
+                                .collect_view()}
                         </div>
                     </div>
                 </div>
@@ -0,0 +1,133 @@
+//! The library page: every cached series, with management actions.
+
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+use crate::api::{list_library, DeleteSeries};
+use crate::pager::Pager;
+use crate::toast::use_toasts;
+use crate::types::SeriesData;
+
+#[component]
+pub fn LibraryPage() -> impl IntoView {
+    let toasts = use_toasts();
+    let delete = ServerAction::<DeleteSeries>::new();
+    let page = RwSignal::new(1u64);
+    let series = Resource::new(
+        move || (page.get(), delete.version().get()),
+        |(page, _)| list_library(page),
+    );
+    // The series waiting for the user to confirm its deletion.
+    let confirming = RwSignal::new(None::<SeriesData>);
+    let deleting = StoredValue::new(None::<SeriesData>);
+
+    Effect::new(move |_| {
+        let Some(result) = delete.value().get() else {
+            return;
+        };
+        let Some(series) = deleting.get_value() else {
+            return;
+        };
+        let title = &series.title;
+        match result {
+            Ok(episodes) => toasts.success(format!("Deleted {title} and {episodes} episodes.")),
+            Err(err) => toasts.error_with_retry(
+                format!("Could not delete {title}: {err}"),
+                Callback::new(move |()| {
+                    delete.dispatch(DeleteSeries { id: series.id });
+                }),
+            ),
+        }
+    });
+
+    let on_confirm = move |_| {
+        if let Some(series) = confirming.get_untracked() {
+            delete.dispatch(DeleteSeries { id: series.id });
+            deleting.set_value(Some(series));
+        }
+        confirming.set(None);
+    };
+
+    view! {
+        <div class="max-w-4xl mx-auto p-4 space-y-4">
+            <h1 class="text-3xl font-bold">"Library"</h1>
+            <Transition fallback=|| view! { <span class="loading loading-spinner"></span> }>
+                {move || Suspend::new(async move {
+                    match series.await {
+                        Ok(series) if series.total == 0 => view! {
+                            <p class="opacity-70">"No series cached yet."</p>
+                        }.into_any(),
+                        Ok(series) => {
+                            // Deleting the last series on the last page leaves
+                            // that page empty; step back to one that exists.
+                            if series.items.is_empty() && series.page > series.total_pages {
+                                page.set(series.total_pages);
+                            }
+                            let total_pages = series.total_pages;
+                            view! {
+                                <table class="table">
+                                    <thead>
+                                        <tr>
+                                            <th>"Title"</th>
+                                            <th>"Slug"</th>
+                                            <th></th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {series.items.into_iter().map(|item| {
+                                            let target = item.clone();
+                                            view! {
+                                                <tr>
+                                                    <td>
+                                                        <A href=format!("/series/{}", item.slug) attr:class="link link-hover">
+                                                            {item.title}
+                                                        </A>
+                                                    </td>
+                                                    <td class="font-mono text-sm">{item.slug}</td>
+                                                    <td class="text-right">
+                                                        <button
+                                                            class="btn btn-sm btn-error btn-outline"
+                                                            disabled=move || delete.pending().get()
+                                                            on:click=move |_| confirming.set(Some(target.clone()))
+                                                        >
+                                                            "Delete"
+                                                        </button>
+                                                    </td>
+                                                </tr>
+                                            }
+                                        }).collect_view()}
+                                    </tbody>
+                                </table>
+                                <Pager
+                                    page=page
+                                    total_pages=total_pages
+                                    on_change=Callback::new(move |next| page.set(next))
+                                />
+                            }.into_any()
+                        }
+                        Err(err) => view! {
+                            <div role="alert" class="alert alert-error">{err.to_string()}</div>
+                        }.into_any(),
+                    }
+                })}
+            </Transition>
+
+            <dialog class="modal" class:modal-open=move || confirming.with(Option::is_some)>
+                <div class="modal-box">
+                    <h3 class="text-lg font-bold">
+                        {move || confirming.with(|series| {
+                            series.as_ref().map(|series| format!("Delete {}?", series.title))
+                        })}
+                    </h3>
+                    <p class="py-4">
+                        "This removes the series and all of its episodes. It cannot be undone."
+                    </p>
+                    <div class="modal-action">
+                        <button class="btn" on:click=move |_| confirming.set(None)>"Cancel"</button>
+                        <button class="btn btn-error" on:click=on_confirm>"Delete"</button>
+                    </div>
+                </div>
+            </dialog>
+        </div>
+    }
+}
@@ -0,0 +1,41 @@
+//! Previous/next controls for anything backed by a [`crate::types::Page`].
+
+use leptos::prelude::*;
+
+/// Renders "« Page X of Y »" and calls `on_change` with the requested
+/// 1-based page. Hidden when everything fits on one page.
+#[component]
+pub fn Pager(
+    #[prop(into)] page: Signal<u64>,
+    #[prop(into)] total_pages: Signal<u64>,
+    on_change: Callback<u64>,
+) -> impl IntoView {
+    let has_prev = move || page.get() > 1;
+    let has_next = move || page.get() < total_pages.get();
+
+    view! {
+        <Show when=move || { total_pages.get() > 1 }>
+            <div class="join">
+                <button
+                    class="join-item btn btn-sm"
+                    aria-label="Previous page"
+                    disabled=move || !has_prev()
+                    on:click=move |_| on_change.run(page.get_untracked() - 1)
+                >
+                    "«"
+                </button>
+                <span class="join-item btn btn-sm btn-disabled">
+                    {move || format!("Page {} of {}", page.get(), total_pages.get())}
+                </span>
+                <button
+                    class="join-item btn btn-sm"
+                    aria-label="Next page"
+                    disabled=move || !has_next()
+                    on:click=move |_| on_change.run(page.get_untracked() + 1)
+                >
+                    "»"
+                </button>
+            </div>
+        </Show>
+    }
+}
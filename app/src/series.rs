@@ -0,0 +1,283 @@
+//! The series page: a series' episode table with inline editing.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_params_map;
+use uuid::Uuid;
+
+use crate::api::{get_series, UpdateEpisode};
+use crate::export::FillerRanges;
+use crate::stats::{SeriesStats, StatsCard};
+use crate::timeline::EpisodeTimeline;
+use crate::toast::use_toasts;
+use crate::types::{EpisodeData, EpisodeEdit, EpisodeType};
+use crate::validation::validate_slug;
+
+/// daisyUI badge colour for each classification.
+fn type_badge_class(ty: EpisodeType) -> &'static str {
+    match ty {
+        EpisodeType::Canon => "badge badge-success",
+        EpisodeType::MixedCanon => "badge badge-info",
+        EpisodeType::Filler => "badge badge-error",
+        EpisodeType::AnimeCanon => "badge badge-warning",
+        EpisodeType::Unknown => "badge badge-ghost",
+    }
+}
+
+/// Copies `text` to the system clipboard. Only the browser build can.
+async fn copy_to_clipboard(text: String) -> Result<(), String> {
+    #[cfg(feature = "hydrate")]
+    {
+        let promise = window().navigator().clipboard().write_text(&text);
+        wasm_bindgen_futures::JsFuture::from(promise)
+            .await
+            .map(|_| ())
+            .map_err(|err| format!("{err:?}"))
+    }
+    #[cfg(not(feature = "hydrate"))]
+    {
+        let _ = text;
+        Err("the clipboard is only available in the browser".to_string())
+    }
+}
+
+#[component]
+pub fn SeriesPage() -> impl IntoView {
+    let params = use_params_map();
+    let slug = move || params.read().get("slug").unwrap_or_default();
+
+    let toasts = use_toasts();
+    let update = ServerAction::<UpdateEpisode>::new();
+    let series = Resource::new(
+        move || (slug(), update.version().get()),
+        |(slug, _)| async move {
+            // A malformed slug can't match a series; say why without asking.
+            validate_slug(&slug).map_err(ServerFnError::new)?;
+            get_series(slug).await
+        },
+    );
+    // The episode whose row is currently in edit mode.
+    let editing = RwSignal::new(None::<Uuid>);
+
+    Effect::new(move |_| match update.value().get() {
+        Some(Ok(episode)) => {
+            toasts.success(format!("Saved episode {}.", episode.number));
+            editing.set(None);
+        }
+        Some(Err(err)) => toasts.error(format!("Could not save the episode: {err}")),
+        None => {}
+    });
+
+    view! {
+        <div class="max-w-5xl mx-auto p-4 space-y-4">
+            <Transition fallback=|| view! { <span class="loading loading-spinner"></span> }>
+                {move || Suspend::new(async move {
+                    match series.await {
+                        Ok(detail) => view! {
+                            <div class="flex items-center justify-between gap-2">
+                                <h1 class="text-3xl font-bold">{detail.series.title}</h1>
+                                <a
+                                    class="btn btn-sm btn-outline"
+                                    href=format!(
+                                        "{}/api/v1/series/{}/guide.md",
+                                        crate::base_path(),
+                                        detail.series.slug,
+                                    )
+                                    rel="external"
+                                    download
+                                >
+                                    "Episode guide (.md)"
+                                </a>
+                            </div>
+                            <StatsCard stats=SeriesStats::from_episodes(&detail.episodes)/>
+                            <EpisodeTimeline episodes=detail.episodes.clone()/>
+                            <FillerRangesCopy ranges=FillerRanges::from_episodes(&detail.episodes)/>
+                            <table class="table">
+                                <thead>
+                                    <tr>
+                                        <th>"#"</th>
+                                        <th>"Title"</th>
+                                        <th>"Type"</th>
+                                        <th>"Notes"</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {detail.episodes.into_iter().map(|episode| {
+                                        view! { <EpisodeRow episode editing update/> }
+                                    }).collect_view()}
+                                </tbody>
+                            </table>
+                        }.into_any(),
+                        Err(err) => view! {
+                            <div role="alert" class="alert alert-error">{err.to_string()}</div>
+                        }.into_any(),
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+/// The filler ranges in the format skip scripts take, with a copy button.
+#[component]
+fn FillerRangesCopy(ranges: FillerRanges) -> impl IntoView {
+    let toasts = use_toasts();
+    let text = ranges.text;
+    let on_copy = {
+        let text = text.clone();
+        move |_| {
+            let text = text.clone();
+            spawn_local(async move {
+                match copy_to_clipboard(text).await {
+                    Ok(()) => toasts.success("Copied the filler ranges."),
+                    Err(err) => toasts.error(format!("Could not copy: {err}")),
+                }
+            });
+        }
+    };
+
+    (!text.is_empty()).then(|| {
+        view! {
+            <div class="flex items-center gap-2">
+                <span class="text-sm opacity-70">"Filler episodes"</span>
+                <code class="text-sm">{text}</code>
+                <button class="btn btn-xs btn-ghost" on:click=on_copy>"Copy"</button>
+            </div>
+        }
+    })
+}
+
+#[component]
+fn EpisodeRow(
+    episode: EpisodeData,
+    editing: RwSignal<Option<Uuid>>,
+    update: ServerAction<UpdateEpisode>,
+) -> impl IntoView {
+    let id = episode.id;
+    let is_editing = move || editing.get() == Some(id);
+
+    let loaded_title = episode.title.clone().unwrap_or_default();
+    let loaded_notes = episode.notes.clone().unwrap_or_default();
+    let title = RwSignal::new(loaded_title.clone());
+    let scraped_type = episode.overridden_from.unwrap_or(episode.episode_type);
+    let loaded_override = episode.overridden_from.map(|_| episode.episode_type);
+    let override_type = RwSignal::new(loaded_override);
+    let notes = RwSignal::new(loaded_notes.clone());
+    let loaded = StoredValue::new((loaded_title, loaded_notes));
+
+    // Discards unsaved input so the next Edit starts from the stored values.
+    let on_cancel = move |_| {
+        let (loaded_title, loaded_notes) = loaded.get_value();
+        title.set(loaded_title);
+        notes.set(loaded_notes);
+        override_type.set(loaded_override);
+        editing.set(None);
+    };
+
+    // Untouched text fields are left out, so a type-only correction doesn't
+    // mark the title as edited and shield it from re-scrapes.
+    let on_save = move |_| {
+        let (loaded_title, loaded_notes) = loaded.get_value();
+        let changed = |value: String, loaded: String| (value != loaded).then_some(value);
+        update.dispatch(UpdateEpisode {
+            id,
+            edit: EpisodeEdit {
+                title: changed(title.get_untracked(), loaded_title),
+                override_type: override_type.get_untracked(),
+                notes: changed(notes.get_untracked(), loaded_notes),
+            },
+        });
+    };
+
+    let display = episode.clone();
+    let view_mode = move || {
+        let episode = display.clone();
+        view! {
+            <td>
+                {episode.title.unwrap_or_default()}
+                {episode.edited_by_user.then(|| view! {
+                    <span class="badge badge-outline badge-sm ml-2" title="Corrected by hand; re-scrapes keep it">
+                        "edited"
+                    </span>
+                })}
+            </td>
+            <td class="whitespace-nowrap">
+                <span class=type_badge_class(episode.episode_type)>{episode.episode_type.as_str()}</span>
+                {episode.overridden_from.map(|scraped| view! {
+                    <span class="text-xs opacity-60 ml-2" title="Classification from the scrape source">
+                        "scraped: " {scraped.as_str()}
+                    </span>
+                })}
+            </td>
+            <td class="text-sm opacity-70">{episode.notes.unwrap_or_default()}</td>
+            <td class="text-right">
+                <button class="btn btn-sm btn-ghost" on:click=move |_| editing.set(Some(id))>
+                    "Edit"
+                </button>
+            </td>
+        }
+        .into_any()
+    };
+
+    let edit_mode = move || {
+        view! {
+            <td>
+                <input
+                    class="input input-bordered input-sm w-full"
+                    prop:value=move || title.get()
+                    on:input=move |ev| title.set(event_target_value(&ev))
+                />
+            </td>
+            <td>
+                <select
+                    class="select select-bordered select-sm"
+                    on:change=move |ev| override_type.set(event_target_value(&ev).parse().ok())
+                >
+                    <option value="" selected=move || override_type.get().is_none()>
+                        {format!("as scraped ({scraped_type})")}
+                    </option>
+                    {EpisodeType::ALL.map(|ty| view! {
+                        <option value=ty.as_str() selected=move || override_type.get() == Some(ty)>
+                            {ty.as_str()}
+                        </option>
+                    })}
+                </select>
+            </td>
+            <td>
+                <input
+                    class="input input-bordered input-sm w-full"
+                    placeholder="Notes"
+                    prop:value=move || notes.get()
+                    on:input=move |ev| notes.set(event_target_value(&ev))
+                />
+            </td>
+            <td class="text-right whitespace-nowrap">
+                <button
+                    class="btn btn-sm btn-primary"
+                    disabled=move || update.pending().get()
+                    on:click=on_save
+                >
+                    "Save"
+                </button>
+                <button class="btn btn-sm btn-ghost" on:click=on_cancel>
+                    "Cancel"
+                </button>
+            </td>
+        }
+        .into_any()
+    };
+
+    view! {
+        <tr id=episode.permalink.clone() class="scroll-mt-4 target:bg-base-200">
+            <td>
+                <a class="link link-hover" href=format!("#{}", episode.permalink) title="Link to this episode">
+                    {episode.number}
+                </a>
+            </td>
+            <Show when=is_editing fallback=view_mode>
+                {edit_mode()}
+            </Show>
+        </tr>
+    }
+}
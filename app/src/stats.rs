@@ -0,0 +1,291 @@
+//! Per-series statistics and the card that shows them on the series page.
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::export::type_counts;
+use crate::types::{EpisodeData, EpisodeType};
+
+/// How much of a series one classification makes up.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeShare {
+    pub episode_type: EpisodeType,
+    pub count: usize,
+    /// Share of all episodes, 0–100.
+    pub percent: f64,
+}
+
+impl TypeShare {
+    /// Shares for per-type episode counts.
+    pub fn from_counts(counts: impl IntoIterator<Item = (EpisodeType, usize)>) -> Vec<Self> {
+        let counts: Vec<_> = counts.into_iter().collect();
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        counts
+            .into_iter()
+            .map(|(episode_type, count)| TypeShare {
+                episode_type,
+                count,
+                percent: if total == 0 {
+                    0.0
+                } else {
+                    count as f64 * 100.0 / total as f64
+                },
+            })
+            .collect()
+    }
+}
+
+/// A run of consecutive episode numbers, both ends inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EpisodeRange {
+    pub start: i32,
+    pub end: i32,
+}
+
+impl EpisodeRange {
+    /// Number of episodes in the range.
+    pub fn count(&self) -> usize {
+        (self.end - self.start) as usize + 1
+    }
+}
+
+/// `26-32`, or just `50` for a single episode.
+impl fmt::Display for EpisodeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SeriesStats {
+    pub total: usize,
+    /// One entry per type, in `EpisodeType::ALL` order.
+    pub types: Vec<TypeShare>,
+    /// The longest run of consecutive filler episodes, if there are any.
+    pub longest_filler_streak: Option<EpisodeRange>,
+}
+
+impl SeriesStats {
+    /// Computed from the effective classification, so overrides count.
+    pub fn from_episodes(episodes: &[EpisodeData]) -> Self {
+        Self {
+            total: episodes.len(),
+            types: TypeShare::from_counts(type_counts(episodes)),
+            longest_filler_streak: filler_ranges(episodes)
+                .into_iter()
+                .max_by_key(|run| (run.count(), -run.start)),
+        }
+    }
+
+    pub fn share(&self, episode_type: EpisodeType) -> Option<&TypeShare> {
+        self.types
+            .iter()
+            .find(|share| share.episode_type == episode_type)
+    }
+}
+
+/// The runs of consecutive filler episodes, in episode order. Uses the
+/// effective classification, so overrides count.
+pub fn filler_ranges(episodes: &[EpisodeData]) -> Vec<EpisodeRange> {
+    let mut filler: Vec<i32> = episodes
+        .iter()
+        .filter(|ep| ep.episode_type == EpisodeType::Filler)
+        .map(|ep| ep.number)
+        .collect();
+    filler.sort_unstable();
+    filler.dedup();
+    runs(&filler).collect()
+}
+
+/// Splits sorted, de-duplicated episode numbers into consecutive runs.
+fn runs(numbers: &[i32]) -> impl Iterator<Item = EpisodeRange> + '_ {
+    numbers.chunk_by(|a, b| b - a == 1).map(|run| EpisodeRange {
+        start: run[0],
+        end: run[run.len() - 1],
+    })
+}
+
+/// Tailwind fill for a classification's SVG bar segment.
+pub fn type_fill_class(ty: EpisodeType) -> &'static str {
+    match ty {
+        EpisodeType::Canon => "fill-success",
+        EpisodeType::MixedCanon => "fill-info",
+        EpisodeType::Filler => "fill-error",
+        EpisodeType::AnimeCanon => "fill-warning",
+        EpisodeType::Unknown => "fill-neutral",
+    }
+}
+
+/// Non-empty shares paired with where their bar segment starts, 0–100.
+fn segments(types: &[TypeShare]) -> impl Iterator<Item = (TypeShare, f64)> + '_ {
+    types
+        .iter()
+        .filter(|share| share.count > 0)
+        .scan(0.0, |offset, share| {
+            let start = *offset;
+            *offset += share.percent;
+            Some((*share, start))
+        })
+}
+
+#[component]
+pub fn StatsCard(stats: SeriesStats) -> impl IntoView {
+    let share = |ty| stats.share(ty).copied();
+    let stat = move |label: &'static str, ty: EpisodeType| {
+        share(ty).map(|share| {
+            view! {
+                <div class="stat">
+                    <div class="stat-title">{label}</div>
+                    <div class="stat-value text-2xl">{share.count}</div>
+                    <div class="stat-desc">{format!("{:.0}%", share.percent)}</div>
+                </div>
+            }
+        })
+    };
+    let streak = match stats.longest_filler_streak {
+        Some(run) if run.start == run.end => format!("episode {}", run.start),
+        Some(run) => format!("episodes {}–{}", run.start, run.end),
+        None => "no filler".to_string(),
+    };
+
+    view! {
+        <div class="card bg-base-200">
+            <div class="card-body gap-4">
+                <div class="stats stats-vertical lg:stats-horizontal bg-base-200">
+                    <div class="stat">
+                        <div class="stat-title">"Episodes"</div>
+                        <div class="stat-value text-2xl">{stats.total}</div>
+                    </div>
+                    {stat("Canon", EpisodeType::Canon)}
+                    {stat("Mixed", EpisodeType::MixedCanon)}
+                    {stat("Filler", EpisodeType::Filler)}
+                    <div class="stat">
+                        <div class="stat-title">"Longest filler streak"</div>
+                        <div class="stat-value text-2xl">
+                            {stats.longest_filler_streak.map_or(0, |run| run.count())}
+                        </div>
+                        <div class="stat-desc">{streak}</div>
+                    </div>
+                </div>
+                <TypeBar types=stats.types/>
+            </div>
+        </div>
+    }
+}
+
+/// A stacked bar with one segment per classification, sized by share.
+#[component]
+pub fn TypeBar(types: Vec<TypeShare>) -> impl IntoView {
+    view! {
+        // SVG geometry rather than inline `style` widths, which the
+        // content security policy blocks.
+        <svg
+            class="block h-3 w-full overflow-hidden rounded-full fill-base-300"
+            viewBox="0 0 100 1"
+            preserveAspectRatio="none"
+        >
+            <rect width="100" height="1"></rect>
+            {segments(&types)
+                .map(|(share, offset)| {
+                    view! {
+                        <rect
+                            class=type_fill_class(share.episode_type)
+                            x=offset
+                            width=share.percent
+                            height="1"
+                        >
+                            <title>
+                                {format!(
+                                    "{}: {} ({:.0}%)",
+                                    share.episode_type,
+                                    share.count,
+                                    share.percent,
+                                )}
+                            </title>
+                        </rect>
+                    }
+                })
+                .collect_view()}
+        </svg>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn episode(number: i32, episode_type: EpisodeType) -> EpisodeData {
+        EpisodeData {
+            id: Uuid::nil(),
+            permalink: String::new(),
+            series_id: Uuid::nil(),
+            number,
+            episode_type,
+            overridden_from: None,
+            title: None,
+            notes: None,
+            edited_by_user: false,
+        }
+    }
+
+    #[test]
+    fn counts_and_percentages() {
+        let episodes = [
+            episode(1, EpisodeType::Canon),
+            episode(2, EpisodeType::Canon),
+            episode(3, EpisodeType::Filler),
+            episode(4, EpisodeType::MixedCanon),
+        ];
+        let stats = SeriesStats::from_episodes(&episodes);
+
+        assert_eq!(stats.total, 4);
+        let canon = stats.share(EpisodeType::Canon).unwrap();
+        assert_eq!(canon.count, 2);
+        assert_eq!(canon.percent, 50.0);
+        assert_eq!(stats.share(EpisodeType::AnimeCanon).unwrap().count, 0);
+    }
+
+    #[test]
+    fn longest_filler_streak_prefers_the_earliest_tie() {
+        let mut episodes: Vec<_> = (1..=12).map(|n| episode(n, EpisodeType::Canon)).collect();
+        for n in [3, 4, 7, 8, 10] {
+            episodes[n as usize - 1].episode_type = EpisodeType::Filler;
+        }
+        let stats = SeriesStats::from_episodes(&episodes);
+
+        assert_eq!(
+            stats.longest_filler_streak,
+            Some(EpisodeRange { start: 3, end: 4 })
+        );
+    }
+
+    #[test]
+    fn filler_ranges_merge_consecutive_episodes() {
+        let mut episodes: Vec<_> = (1..=12).map(|n| episode(n, EpisodeType::Canon)).collect();
+        for n in [3, 4, 5, 8, 11, 12] {
+            episodes[n as usize - 1].episode_type = EpisodeType::Filler;
+        }
+        let ranges: Vec<_> = filler_ranges(&episodes)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(ranges, ["3-5", "8", "11-12"]);
+    }
+
+    #[test]
+    fn empty_series() {
+        let stats = SeriesStats::from_episodes(&[]);
+
+        assert_eq!(stats.total, 0);
+        assert_eq!(stats.longest_filler_streak, None);
+        assert!(stats.types.iter().all(|share| share.percent == 0.0));
+    }
+}
@@ -0,0 +1,47 @@
+//! A colour-coded strip showing where filler falls in a series.
+
+use leptos::prelude::*;
+
+use crate::types::{EpisodeData, EpisodeType};
+
+/// Tailwind background for a classification's timeline segment.
+fn type_bg_class(ty: EpisodeType) -> &'static str {
+    match ty {
+        EpisodeType::Canon => "bg-success",
+        EpisodeType::MixedCanon => "bg-info",
+        EpisodeType::Filler => "bg-error",
+        EpisodeType::AnimeCanon => "bg-warning",
+        EpisodeType::Unknown => "bg-neutral",
+    }
+}
+
+/// One equal-width segment per episode, in the order given. Each segment
+/// links to the episode's row on the series page, anchored by permalink.
+#[component]
+pub fn EpisodeTimeline(episodes: Vec<EpisodeData>) -> impl IntoView {
+    view! {
+        <nav class="flex h-6 w-full overflow-hidden rounded" aria-label="Episode timeline">
+            {episodes
+                .into_iter()
+                .map(|episode| {
+                    let tooltip = match episode.title.as_deref() {
+                        Some(title) => {
+                            format!("{}. {title} ({})", episode.number, episode.episode_type)
+                        }
+                        None => format!("{} ({})", episode.number, episode.episode_type),
+                    };
+                    view! {
+                        <a
+                            class=format!(
+                                "min-w-0 flex-1 hover:opacity-60 {}",
+                                type_bg_class(episode.episode_type),
+                            )
+                            href=format!("#{}", episode.permalink)
+                            title=tooltip
+                        ></a>
+                    }
+                })
+                .collect_view()}
+        </nav>
+    }
+}
@@ -0,0 +1,145 @@
+//! Global toast notifications.
+//!
+//! [`provide_toasts`] is called once in [`crate::App`]; any component below
+//! it can grab the queue with [`use_toasts`] and push messages that the
+//! [`Toaster`] renders in the corner of the screen.
+
+use leptos::prelude::*;
+use std::time::Duration;
+
+/// How long success and info toasts stay on screen. Errors stay until they
+/// are dismissed or retried.
+const AUTO_DISMISS: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Error,
+}
+
+impl ToastKind {
+    fn alert_class(self) -> &'static str {
+        match self {
+            ToastKind::Info => "alert alert-info",
+            ToastKind::Success => "alert alert-success",
+            ToastKind::Error => "alert alert-error",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Toast {
+    id: u64,
+    kind: ToastKind,
+    message: String,
+    retry: Option<Callback<()>>,
+}
+
+/// Handle to the notification queue, cheap to copy into event handlers.
+#[derive(Clone, Copy)]
+pub struct Toasts {
+    items: RwSignal<Vec<Toast>>,
+    next_id: StoredValue<u64>,
+}
+
+impl Toasts {
+    pub fn info(&self, message: impl Into<String>) {
+        self.push(ToastKind::Info, message.into(), None);
+    }
+
+    pub fn success(&self, message: impl Into<String>) {
+        self.push(ToastKind::Success, message.into(), None);
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.push(ToastKind::Error, message.into(), None);
+    }
+
+    /// An error toast with a "Retry" button that dismisses the toast and
+    /// runs `retry`.
+    pub fn error_with_retry(&self, message: impl Into<String>, retry: Callback<()>) {
+        self.push(ToastKind::Error, message.into(), Some(retry));
+    }
+
+    pub fn dismiss(&self, id: u64) {
+        self.items.update(|items| items.retain(|toast| toast.id != id));
+    }
+
+    fn push(&self, kind: ToastKind, message: String, retry: Option<Callback<()>>) {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+        self.items.update(|items| {
+            items.push(Toast {
+                id,
+                kind,
+                message,
+                retry,
+            })
+        });
+
+        if kind != ToastKind::Error {
+            let toasts = *self;
+            set_timeout(move || toasts.dismiss(id), AUTO_DISMISS);
+        }
+    }
+}
+
+pub fn provide_toasts() -> Toasts {
+    let toasts = Toasts {
+        items: RwSignal::new(Vec::new()),
+        next_id: StoredValue::new(0),
+    };
+    provide_context(toasts);
+    toasts
+}
+
+/// The notification queue provided by [`provide_toasts`].
+pub fn use_toasts() -> Toasts {
+    expect_context::<Toasts>()
+}
+
+/// Renders the current toasts. Mount once, near the root of the app.
+#[component]
+pub fn Toaster() -> impl IntoView {
+    let toasts = use_toasts();
+
+    view! {
+        <div class="toast toast-end z-50">
+            <For
+                each=move || toasts.items.get()
+                key=|toast| toast.id
+                children=move |toast| {
+                    let id = toast.id;
+                    let retry = toast.retry.map(|retry| {
+                        view! {
+                            <button
+                                class="btn btn-sm"
+                                on:click=move |_| {
+                                    toasts.dismiss(id);
+                                    retry.run(());
+                                }
+                            >
+                                "Retry"
+                            </button>
+                        }
+                    });
+
+                    view! {
+                        <div role="alert" class=toast.kind.alert_class()>
+                            <span>{toast.message}</span>
+                            {retry}
+                            <button
+                                class="btn btn-sm btn-ghost"
+                                aria-label="Dismiss"
+                                on:click=move |_| toasts.dismiss(id)
+                            >
+                                "✕"
+                            </button>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
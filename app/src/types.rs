@@ -0,0 +1,273 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+use crate::validation::MAX_PAGE;
+
+/// Classification of an episode, mirrored from `entity::episode::EpisodeType`
+/// so it can be used on the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum EpisodeType {
+    Canon,
+    MixedCanon,
+    Filler,
+    AnimeCanon,
+    /// The source used a label we do not recognize.
+    Unknown,
+}
+
+impl EpisodeType {
+    pub const ALL: [EpisodeType; 5] = [
+        EpisodeType::Canon,
+        EpisodeType::MixedCanon,
+        EpisodeType::Filler,
+        EpisodeType::AnimeCanon,
+        EpisodeType::Unknown,
+    ];
+
+    /// The identifier stored in the database and accepted in query strings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EpisodeType::Canon => "canon",
+            EpisodeType::MixedCanon => "mixed",
+            EpisodeType::Filler => "filler",
+            EpisodeType::AnimeCanon => "anime_canon",
+            EpisodeType::Unknown => "unknown",
+        }
+    }
+
+    /// Human-readable name, for documents meant to be read rather than
+    /// parsed.
+    pub fn label(self) -> &'static str {
+        match self {
+            EpisodeType::Canon => "Canon",
+            EpisodeType::MixedCanon => "Mixed Canon",
+            EpisodeType::Filler => "Filler",
+            EpisodeType::AnimeCanon => "Anime Canon",
+            EpisodeType::Unknown => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for EpisodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EpisodeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EpisodeType::ALL
+            .into_iter()
+            .find(|ty| ty.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown episode type `{s}`"))
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<entity::episode::EpisodeType> for EpisodeType {
+    fn from(ty: entity::episode::EpisodeType) -> Self {
+        use entity::episode::EpisodeType as Db;
+        match ty {
+            Db::Canon => EpisodeType::Canon,
+            Db::MixedCanon => EpisodeType::MixedCanon,
+            Db::Filler => EpisodeType::Filler,
+            Db::AnimeCanon => EpisodeType::AnimeCanon,
+            Db::Unknown => EpisodeType::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<EpisodeType> for entity::episode::EpisodeType {
+    fn from(ty: EpisodeType) -> Self {
+        use entity::episode::EpisodeType as Db;
+        match ty {
+            EpisodeType::Canon => Db::Canon,
+            EpisodeType::MixedCanon => Db::MixedCanon,
+            EpisodeType::Filler => Db::Filler,
+            EpisodeType::AnimeCanon => Db::AnimeCanon,
+            EpisodeType::Unknown => Db::Unknown,
+        }
+    }
+}
+
+/// Envelope shared by every list endpoint: one 1-based page of results plus
+/// what a client needs to fetch the next one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total: u64,
+    pub total_pages: u64,
+    /// Opaque token for the next page, passed back as `?cursor=`. `None` on
+    /// the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, page: u64, per_page: u64, total: u64) -> Self {
+        let total_pages = total.div_ceil(per_page.max(1));
+        Self {
+            items,
+            page,
+            per_page,
+            total,
+            total_pages,
+            next_cursor: (page < total_pages).then(|| encode_cursor(page + 1)),
+        }
+    }
+
+    pub fn empty(page: u64, per_page: u64) -> Self {
+        Self::new(Vec::new(), page, per_page, 0)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            per_page: self.per_page,
+            total: self.total,
+            total_pages: self.total_pages,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Cursors currently wrap the page number; clients must treat them as
+/// opaque so keyset pagination can replace this without an API change.
+fn encode_cursor(page: u64) -> String {
+    format!("p{page}")
+}
+
+/// The page number a cursor from [`Page::next_cursor`] points at. Pages past
+/// [`MAX_PAGE`] are never handed out, so they are rejected rather than
+/// clamped.
+pub fn decode_cursor(cursor: &str) -> Result<u64, String> {
+    cursor
+        .strip_prefix('p')
+        .and_then(|page| page.parse().ok())
+        .filter(|page| (1..=MAX_PAGE).contains(page))
+        .ok_or_else(|| format!("invalid cursor `{cursor}`"))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SeriesData {
+    pub id: Uuid,
+    pub slug: String,
+    pub title: String,
+    pub last_fetched: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "ssr")]
+impl From<entity::series::Model> for SeriesData {
+    fn from(model: entity::series::Model) -> Self {
+        Self {
+            id: model.id,
+            slug: model.slug,
+            title: model.title,
+            last_fetched: model.last_fetched.map(|at| at.to_utc()),
+        }
+    }
+}
+
+/// Stable public identifier for an episode: 12 hex digits of a 64-bit
+/// FNV-1a hash over `<slug>/<number>`. Unlike the row UUID it comes out the
+/// same when a series is deleted and scraped again.
+pub fn episode_permalink(slug: &str, number: i32) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = format!("{slug}/{number}")
+        .bytes()
+        .fold(OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+    format!("{:012x}", hash & 0xffff_ffff_ffff)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EpisodeData {
+    pub id: Uuid,
+    /// See [`episode_permalink`]. Use this, not `id`, in links.
+    #[serde(default)]
+    pub permalink: String,
+    pub series_id: Uuid,
+    pub number: i32,
+    /// The effective classification: a user override if there is one,
+    /// otherwise what the scrape source reported.
+    pub episode_type: EpisodeType,
+    /// What the scrape source reported, present only when a user override
+    /// replaced it.
+    #[serde(default)]
+    pub overridden_from: Option<EpisodeType>,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// The title was corrected by hand and is kept by re-scrapes.
+    #[serde(default)]
+    pub edited_by_user: bool,
+}
+
+/// A user's correction to an episode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeEdit {
+    /// The new title; `None` keeps the current one and a blank one clears it.
+    pub title: Option<String>,
+    /// Replaces the scraped classification; `None` goes back to it.
+    pub override_type: Option<EpisodeType>,
+    /// The new notes, with the same `None` and blank rules as `title`.
+    pub notes: Option<String>,
+}
+
+#[cfg(feature = "ssr")]
+impl From<entity::episode::Model> for EpisodeData {
+    fn from(model: entity::episode::Model) -> Self {
+        Self {
+            id: model.id,
+            permalink: model.permalink.unwrap_or_default(),
+            series_id: model.show_id,
+            number: model.episode_num,
+            episode_type: model
+                .override_type
+                .clone()
+                .unwrap_or_else(|| model.episode_type.clone())
+                .into(),
+            overridden_from: model
+                .override_type
+                .is_some()
+                .then(|| model.episode_type.into()),
+            title: model.title,
+            notes: model.notes,
+            edited_by_user: model.edited_by_user,
+        }
+    }
+}
+
+/// What restoring a database snapshot wrote.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ImportReport {
+    pub series: u64,
+    pub episodes: u64,
+}
+
+/// What a series merge did with the source series' episodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// Episodes re-parented onto the target.
+    pub moved: u64,
+    /// Episodes the target already had, merged into its rows.
+    pub collisions: u64,
+    /// Numbers of colliding episodes whose source notes or type override
+    /// were dropped because the target's row had its own.
+    pub discarded: Vec<i32>,
+}
@@ -0,0 +1,347 @@
+//! Input validation shared by the server and the hydrated client.
+//!
+//! Everything here compiles for both targets, so forms can check input
+//! before hitting the network with exactly the rules the server enforces.
+
+use std::fmt;
+
+const AFL_HOST: &str = "animefillerlist.com";
+const ANIDB_HOST: &str = "anidb.net";
+
+pub const DEFAULT_PER_PAGE: u64 = 25;
+pub const MAX_PER_PAGE: u64 = 100;
+/// Highest page number served, so the row offset of even the largest page
+/// fits the database's signed 64-bit `OFFSET`.
+pub const MAX_PAGE: u64 = i64::MAX as u64 / MAX_PER_PAGE;
+
+/// A 1-based page number within `1..=MAX_PAGE`, defaulting to the first
+/// page.
+pub fn clamp_page(page: Option<u64>) -> u64 {
+    page.unwrap_or(1).clamp(1, MAX_PAGE)
+}
+
+/// A page size within `1..=MAX_PER_PAGE`, defaulting to `DEFAULT_PER_PAGE`.
+pub fn clamp_per_page(per_page: Option<u64>) -> u64 {
+    per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+}
+
+/// Where a scrape request should be sent, detected from what the user typed
+/// into the home page input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScrapeSource {
+    /// An animefillerlist.com show, from a `/shows/<slug>` URL, a bare slug
+    /// or a show name like "One Piece".
+    AnimeFillerList { slug: String },
+    /// An AniDB anime, enriched directly without scraping AFL.
+    AniDb { aid: u32 },
+}
+
+impl fmt::Display for ScrapeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScrapeSource::AnimeFillerList { slug } => write!(f, "animefillerlist.com show `{slug}`"),
+            ScrapeSource::AniDb { aid } => write!(f, "AniDB anime {aid}"),
+        }
+    }
+}
+
+/// Whether `slug` looks like an animefillerlist.com show slug: lowercase
+/// ASCII letters, digits and single dashes.
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && !slug.contains("--")
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// [`is_valid_slug`] with a message suitable for showing to the user.
+pub fn validate_slug(slug: &str) -> Result<(), String> {
+    if is_valid_slug(slug) {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{slug}` is not a valid slug (lowercase letters, digits and single dashes)."
+        ))
+    }
+}
+
+/// Whether series can be fetched from `host` (lowercase, without `www.`).
+pub fn is_supported_host(host: &str) -> bool {
+    matches!(host, AFL_HOST | ANIDB_HOST)
+}
+
+/// The ASCII spelling of a Latin letter with a diacritic, lowercased.
+fn fold_latin(c: char) -> Option<&'static str> {
+    let folded = match c.to_lowercase().next()? {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ĺ' | 'ľ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// Turns a show name into the slug animefillerlist.com would use for it:
+/// "Naruto: Shippuden" becomes `naruto-shippuden`. Apostrophes are dropped
+/// rather than split on, so "JoJo's" becomes `jojos`, and common Latin
+/// diacritics are folded, so "Pokémon" becomes `pokemon`.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().filter(|c| !matches!(c, '\'' | '’')) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if let Some(folded) = fold_latin(c) {
+            slug.push_str(folded);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Recognizes animefillerlist.com show URLs, bare slugs, show names and
+/// AniDB anime URLs (`/anime/<aid>` or the legacy
+/// `animedb.pl?show=anime&aid=<aid>`). URLs may leave off the scheme.
+pub fn parse_scrape_input(input: &str) -> Result<ScrapeSource, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Enter a series URL, name or slug.".to_string());
+    }
+
+    // "anidb.net/anime/69" is a URL with the scheme left off, not a show
+    // name; "Dr. Stone" and "Fate/Zero" are names.
+    let url = if looks_like_host(input) {
+        format!("https://{input}")
+    } else {
+        input.to_string()
+    };
+    let Some((host, path, query)) = split_url(&url) else {
+        let slug = slugify(input);
+        return if is_valid_slug(&slug) {
+            Ok(ScrapeSource::AnimeFillerList { slug })
+        } else {
+            Err(format!("`{input}` is not a URL, slug or show name."))
+        };
+    };
+
+    if !is_supported_host(&host) {
+        return Err(format!("Unsupported site `{host}`."));
+    }
+    match host.as_str() {
+        AFL_HOST => {
+            let slug = path
+                .strip_prefix("/shows/")
+                .map(|slug| slug.trim_end_matches('/'))
+                .filter(|slug| is_valid_slug(slug))
+                .ok_or_else(|| {
+                    "animefillerlist.com URLs must point at a show, like /shows/one-piece."
+                        .to_string()
+                })?;
+            Ok(ScrapeSource::AnimeFillerList {
+                slug: slug.to_string(),
+            })
+        }
+        // ANIDB_HOST, the only other supported host.
+        _ => {
+            let aid = path
+                .strip_prefix("/anime/")
+                .map(|aid| aid.trim_end_matches('/'))
+                .or_else(|| {
+                    query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix("aid="))
+                })
+                .and_then(|aid| aid.parse().ok())
+                .ok_or_else(|| "AniDB URLs must point at an anime, like /anime/69.".to_string())?;
+            Ok(ScrapeSource::AniDb { aid })
+        }
+    }
+}
+
+/// Whether `input` starts with something like `example.com`: a dotted first
+/// path segment without spaces.
+fn looks_like_host(input: &str) -> bool {
+    let first = input.split(['/', '?', '#']).next().unwrap_or_default();
+    first.contains('.') && !first.contains(char::is_whitespace)
+}
+
+/// Splits an `http(s)://` URL into its lowercased host (without `www.`),
+/// path and query string. Returns `None` when `input` is not such a URL.
+fn split_url(input: &str) -> Option<(String, &str, &str)> {
+    let rest = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))?;
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let host = host.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").map(str::to_owned).unwrap_or(host);
+
+    Some((host, path, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn afl(slug: &str) -> Result<ScrapeSource, String> {
+        Ok(ScrapeSource::AnimeFillerList {
+            slug: slug.to_string(),
+        })
+    }
+
+    #[test]
+    fn page_clamps() {
+        assert_eq!(clamp_page(None), 1);
+        assert_eq!(clamp_page(Some(0)), 1);
+        assert_eq!(clamp_page(Some(7)), 7);
+        assert_eq!(clamp_page(Some(u64::MAX)), MAX_PAGE);
+        let last_offset = (MAX_PAGE - 1) * MAX_PER_PAGE;
+        assert!(i64::try_from(last_offset).is_ok());
+        assert_eq!(clamp_per_page(None), DEFAULT_PER_PAGE);
+        assert_eq!(clamp_per_page(Some(0)), 1);
+        assert_eq!(clamp_per_page(Some(1000)), MAX_PER_PAGE);
+    }
+
+    #[test]
+    fn slugs() {
+        for slug in ["one-piece", "86", "re-zero-2"] {
+            assert_eq!(validate_slug(slug), Ok(()), "{slug}");
+        }
+        for slug in [
+            "",
+            "-one",
+            "one-",
+            "one--piece",
+            "One-Piece",
+            "one_piece",
+            "ワンピース",
+        ] {
+            assert!(validate_slug(slug).is_err(), "{slug}");
+        }
+    }
+
+    #[test]
+    fn supported_hosts() {
+        assert!(is_supported_host("animefillerlist.com"));
+        assert!(is_supported_host("anidb.net"));
+        assert!(!is_supported_host("www.anidb.net"));
+        assert!(!is_supported_host("myanimelist.net"));
+    }
+
+    #[test]
+    fn split_url_normalizes_the_host() {
+        assert_eq!(
+            split_url("https://WWW.AnimeFillerList.com/shows/one-piece?x=1#top"),
+            Some(("animefillerlist.com".to_string(), "/shows/one-piece", "x=1"))
+        );
+        assert_eq!(
+            split_url("http://anidb.net"),
+            Some(("anidb.net".to_string(), "", ""))
+        );
+        assert_eq!(split_url("one piece"), None);
+        assert_eq!(split_url("ftp://anidb.net/anime/1"), None);
+    }
+
+    #[test]
+    fn animefillerlist_show_urls() {
+        for input in [
+            "https://www.animefillerlist.com/shows/one-piece",
+            "http://animefillerlist.com/shows/one-piece/",
+            "https://animefillerlist.com/shows/one-piece?page=2",
+            "  https://animefillerlist.com/shows/one-piece  ",
+        ] {
+            assert_eq!(parse_scrape_input(input), afl("one-piece"), "{input}");
+        }
+        assert!(parse_scrape_input("https://animefillerlist.com/").is_err());
+        assert!(parse_scrape_input("https://animefillerlist.com/shows/One_Piece").is_err());
+    }
+
+    #[test]
+    fn anidb_anime_urls() {
+        let one_piece = Ok(ScrapeSource::AniDb { aid: 69 });
+        for input in [
+            "https://anidb.net/anime/69",
+            "https://www.anidb.net/anime/69/",
+            "https://anidb.net/anime/69?lang=en",
+            "https://anidb.net/perl-bin/animedb.pl?show=anime&aid=69",
+        ] {
+            assert_eq!(parse_scrape_input(input), one_piece, "{input}");
+        }
+        assert!(parse_scrape_input("https://anidb.net/anime/").is_err());
+        assert!(parse_scrape_input("https://anidb.net/anime/abc").is_err());
+    }
+
+    #[test]
+    fn unsupported_hosts_and_empty_input() {
+        assert_eq!(
+            parse_scrape_input("https://myanimelist.net/anime/21"),
+            Err("Unsupported site `myanimelist.net`.".to_string())
+        );
+        assert!(parse_scrape_input("").is_err());
+        assert!(parse_scrape_input("   ").is_err());
+    }
+
+    #[test]
+    fn bare_names_and_slugs() {
+        assert_eq!(parse_scrape_input("one-piece"), afl("one-piece"));
+        assert_eq!(parse_scrape_input("One Piece"), afl("one-piece"));
+        assert_eq!(
+            parse_scrape_input("Naruto: Shippuden"),
+            afl("naruto-shippuden")
+        );
+        assert_eq!(
+            parse_scrape_input("JoJo's Bizarre Adventure"),
+            afl("jojos-bizarre-adventure")
+        );
+        assert!(parse_scrape_input("!!!").is_err());
+        assert_eq!(parse_scrape_input("Fate/Zero"), afl("fate-zero"));
+        assert_eq!(parse_scrape_input("Dr. Stone"), afl("dr-stone"));
+        assert_eq!(parse_scrape_input("Pokémon"), afl("pokemon"));
+        assert_eq!(
+            parse_scrape_input("SHŌGUN Ōoku"),
+            afl("shogun-ooku")
+        );
+    }
+
+    #[test]
+    fn urls_without_a_scheme() {
+        assert_eq!(
+            parse_scrape_input("animefillerlist.com/shows/one-piece"),
+            afl("one-piece")
+        );
+        assert_eq!(
+            parse_scrape_input("www.anidb.net/anime/69"),
+            Ok(ScrapeSource::AniDb { aid: 69 })
+        );
+        assert_eq!(
+            parse_scrape_input("anidb.net/anime/69"),
+            Ok(ScrapeSource::AniDb { aid: 69 })
+        );
+        assert_eq!(
+            parse_scrape_input("example.com/shows/one-piece"),
+            Err("Unsupported site `example.com`.".to_string())
+        );
+    }
+}
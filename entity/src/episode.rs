@@ -11,6 +11,10 @@ pub enum EpisodeType {
     Filler,
     #[sea_orm(string_value = "anime_canon")]
     AnimeCanon,
+    /// A label the scraper did not recognize; kept for triage instead of
+    /// being guessed as canon.
+    #[sea_orm(string_value = "unknown")]
+    Unknown,
 }
 #[sea_orm::model]
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
@@ -18,12 +22,26 @@ pub enum EpisodeType {
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
+    #[sea_orm(unique_key = "show_episode")]
     pub show_id: Uuid,
     #[sea_orm(belongs_to, from = "show_id", to = "id")]
     pub series: HasOne<super::series::Entity>,
+    #[sea_orm(unique_key = "show_episode")]
     pub episode_num: i32,
+    /// The classification reported by the scrape source.
     pub episode_type: EpisodeType,
+    /// A user's classification, which wins over `episode_type` and is never
+    /// touched by re-scrapes.
+    pub override_type: Option<EpisodeType>,
     pub title: Option<String>,
+    pub notes: Option<String>,
+    /// Public identifier derived from the series slug and episode number, so
+    /// it survives the row being deleted and scraped again.
+    #[sea_orm(unique)]
+    pub permalink: Option<String>,
+    /// Set when a user corrected the title or notes; re-scrapes keep them.
+    #[sea_orm(default_value = false)]
+    pub edited_by_user: bool,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
\ No newline at end of file
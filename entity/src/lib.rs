@@ -4,5 +4,6 @@ pub mod prelude;
 pub mod user;
 pub mod series;
 pub mod episode;
+pub mod permalink_alias;
 
 pub use sea_orm;
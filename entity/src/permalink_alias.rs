@@ -0,0 +1,15 @@
+use sea_orm::entity::prelude::*;
+/// A permalink an episode answered to before a series merge moved it to
+/// another slug. Lookups fall back to these, so old links keep working.
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "permalink_aliases")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub permalink: String,
+    pub episode_id: Uuid,
+    #[sea_orm(belongs_to, from = "episode_id", to = "id", on_delete = "Cascade")]
+    pub episode: HasOne<super::episode::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -3,3 +3,4 @@
 pub use super::user::Entity as User;
 pub use super::series::Entity as Series;
 pub use super::episode::Entity as Episode;
+pub use super::permalink_alias::Entity as PermalinkAlias;
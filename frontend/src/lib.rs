@@ -5,5 +5,10 @@ pub fn hydrate() {
     _ = console_log::init_with_level(log::Level::Debug);
     console_error_panic_hook::set_once();
 
+    // server functions live under the same prefix as the pages
+    if !base_path().is_empty() {
+        leptos::server_fn::client::set_server_url(base_path());
+    }
+
     leptos::mount::hydrate_body(App);
 }
@@ -0,0 +1,268 @@
+use app::db::{EpisodeQuery, EpisodeStore, PageRequest, SeriesQuery, SeriesStore};
+use app::export::ExportFormat;
+use app::types::{EpisodeData, Page, SeriesData};
+use app::validation::validate_slug;
+use axum::{
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use utoipa::{openapi::Server, IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::state::AppState;
+
+mod export;
+
+/// Largest snapshot `/import/snapshot.json` accepts; axum's 2 MB default is
+/// too small for a big library.
+const SNAPSHOT_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// JSON endpoints mounted under `/api/v1`.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/version", get(version))
+        .route("/series", get(list_series))
+        .route("/episodes", get(list_episodes))
+        .route("/episodes/{permalink}", get(get_episode))
+        .route("/series/{slug}/export.md", get(export::series_markdown))
+        .route("/series/{slug}/guide.md", get(export::series_guide))
+        .route("/series/{slug}/filler.txt", get(export::filler_text))
+        .route("/series/{slug}/filler.json", get(export::filler_json))
+        .route("/export/vault.zip", get(export::vault))
+        .route("/export/library.zip", get(export::library))
+        .route("/export/snapshot.json", get(export::snapshot))
+        .route(
+            "/import/snapshot.json",
+            post(export::restore_snapshot).layer(DefaultBodyLimit::max(SNAPSHOT_BODY_LIMIT)),
+        )
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Seiten API", description = "Cached series, episodes, exports and snapshots."),
+    paths(
+        version,
+        list_series,
+        list_episodes,
+        get_episode,
+        export::series_markdown,
+        export::series_guide,
+        export::filler_text,
+        export::filler_json,
+        export::vault,
+        export::library,
+        export::snapshot,
+        export::restore_snapshot,
+    )
+)]
+struct ApiDoc;
+
+/// Swagger UI at `{base}/api/docs`, backed by the generated document at
+/// `{base}/api/docs/openapi.json`.
+pub fn docs(base: &str) -> SwaggerUi {
+    let mut openapi = ApiDoc::openapi();
+    openapi.servers = Some(vec![Server::new(format!("{base}/api/v1"))]);
+
+    SwaggerUi::new(format!("{base}/api/docs"))
+        .url(format!("{base}/api/docs/openapi.json"), openapi)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("database error: {0}")]
+    Database(#[from] DbErr),
+    #[error("export failed: {0}")]
+    Export(String),
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        // Internal errors are logged in full but not echoed back, since
+        // database errors can carry SQL, table names or connection details.
+        let (status, error) = match self {
+            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            ApiError::Database(_) | ApiError::Export(_) => {
+                log::error!("{self}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal server error".to_string(),
+                )
+            }
+        };
+        let body = ErrorBody { error };
+        (status, Json(body)).into_response()
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct VersionInfo {
+    version: &'static str,
+    commit: Option<&'static str>,
+}
+
+/// Reports the running build. `SEITEN_COMMIT` is read at compile time so
+/// release builds can stamp the git revision in.
+#[utoipa::path(get, path = "/version", responses((status = 200, body = VersionInfo)))]
+async fn version() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        commit: option_env!("SEITEN_COMMIT"),
+    })
+}
+
+/// Serializes a response body in the format asked for with `?format=`.
+fn formatted<T: Serialize>(format: Option<ExportFormat>, value: &T) -> Result<Response, ApiError> {
+    let format = format.unwrap_or_default();
+    if format == ExportFormat::Json {
+        return Ok(Json(value).into_response());
+    }
+    let body = format.render(value).map_err(ApiError::Export)?;
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response())
+}
+
+fn parse_optional<T>(value: Option<&str>) -> Result<Option<T>, ApiError>
+where
+    T: std::str::FromStr<Err = String>,
+{
+    value
+        .filter(|value| !value.is_empty())
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SeriesParams {
+    /// 1-based page number.
+    page: Option<u64>,
+    /// Page size, at most 100.
+    per_page: Option<u64>,
+    /// `next_cursor` from a previous page; takes precedence over `page`.
+    cursor: Option<String>,
+    /// `title`, `slug` or `last_fetched`; prefix with `-` for descending.
+    sort: Option<String>,
+    /// Case-insensitive match on title or slug.
+    q: Option<String>,
+    /// `json` (default), `ron`, `yaml` or `toml`.
+    format: Option<String>,
+}
+
+/// List cached series.
+#[utoipa::path(
+    get,
+    path = "/series",
+    params(SeriesParams),
+    responses(
+        (status = 200, body = Page<SeriesData>),
+        (status = 400, body = ErrorBody),
+    )
+)]
+async fn list_series(
+    State(state): State<AppState>,
+    Query(params): Query<SeriesParams>,
+) -> Result<Response, ApiError> {
+    let format = parse_optional(params.format.as_deref())?;
+    let query = SeriesQuery {
+        page: PageRequest::from_params(params.page, params.per_page, params.cursor.as_deref())
+            .map_err(ApiError::BadRequest)?,
+        sort: parse_optional(params.sort.as_deref())?,
+        q: params.q,
+    };
+    let page: Page<SeriesData> = SeriesStore::list(&state.db, &query).await?.map(Into::into);
+    formatted(format, &page)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct EpisodeParams {
+    /// 1-based page number.
+    page: Option<u64>,
+    /// Page size, at most 100.
+    per_page: Option<u64>,
+    /// `next_cursor` from a previous page; takes precedence over `page`.
+    cursor: Option<String>,
+    /// `number`, `title` or `type`; prefix with `-` for descending.
+    sort: Option<String>,
+    /// Only episodes of the series with this slug.
+    series: Option<String>,
+    /// `canon`, `mixed`, `filler`, `anime_canon` or `unknown`.
+    #[serde(rename = "type")]
+    #[param(rename = "type")]
+    episode_type: Option<String>,
+    /// Case-insensitive match on the episode title.
+    q: Option<String>,
+    /// `json` (default), `ron`, `yaml` or `toml`.
+    format: Option<String>,
+}
+
+/// List cached episodes.
+#[utoipa::path(
+    get,
+    path = "/episodes",
+    params(EpisodeParams),
+    responses(
+        (status = 200, body = Page<EpisodeData>),
+        (status = 400, body = ErrorBody),
+    )
+)]
+async fn list_episodes(
+    State(state): State<AppState>,
+    Query(params): Query<EpisodeParams>,
+) -> Result<Response, ApiError> {
+    let format = parse_optional(params.format.as_deref())?;
+    if let Some(slug) = params.series.as_deref() {
+        validate_slug(slug).map_err(ApiError::BadRequest)?;
+    }
+    let query = EpisodeQuery {
+        page: PageRequest::from_params(params.page, params.per_page, params.cursor.as_deref())
+            .map_err(ApiError::BadRequest)?,
+        sort: parse_optional(params.sort.as_deref())?,
+        series: params.series,
+        episode_type: parse_optional(params.episode_type.as_deref())?,
+        q: params.q,
+    };
+    let page: Page<EpisodeData> = EpisodeStore::list(&state.db, &query).await?.map(Into::into);
+    formatted(format, &page)
+}
+
+/// Look up one episode by its permalink.
+///
+/// Permalinks are derived from the series slug and episode number, so they
+/// survive a series being deleted and scraped again.
+#[utoipa::path(
+    get,
+    path = "/episodes/{permalink}",
+    params(("permalink" = String, Path, description = "Episode permalink")),
+    responses(
+        (status = 200, body = EpisodeData),
+        (status = 404, body = ErrorBody),
+    )
+)]
+async fn get_episode(
+    State(state): State<AppState>,
+    Path(permalink): Path<String>,
+) -> Result<Json<EpisodeData>, ApiError> {
+    EpisodeStore::find_by_permalink(&state.db, &permalink)
+        .await?
+        .map(|episode| Json(episode.into()))
+        .ok_or_else(|| ApiError::NotFound(format!("no episode with permalink {permalink}")))
+}
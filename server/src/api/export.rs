@@ -0,0 +1,335 @@
+use app::db::{EpisodeStore, SeriesStore};
+use app::export::{
+    self, DatabaseSnapshot, FillerRanges, LibraryManifest, ManifestEntry, SeriesExport,
+    EXPORT_SCHEMA_VERSION,
+};
+use app::types::{EpisodeData, ImportReport, SeriesData};
+use app::validation::validate_slug;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use entity::series;
+use sea_orm::DatabaseConnection;
+use std::{
+    io::{self, Cursor, Write},
+    sync::{Arc, Mutex},
+};
+use zip::write::{SimpleFileOptions, StreamWriter};
+use zip::ZipWriter;
+
+use super::{ApiError, ErrorBody};
+use crate::state::AppState;
+
+async fn series_with_episodes(
+    state: &AppState,
+    slug: &str,
+) -> Result<(SeriesData, Vec<EpisodeData>), ApiError> {
+    validate_slug(slug).map_err(ApiError::BadRequest)?;
+    let series = SeriesStore::find_by_slug(&state.db, slug)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("no series with slug `{slug}`")))?;
+    let episodes = EpisodeStore::for_series(&state.db, series.id).await?;
+
+    Ok((
+        series.into(),
+        episodes.into_iter().map(EpisodeData::from).collect(),
+    ))
+}
+
+/// A series as a Markdown note with YAML frontmatter (Obsidian/Notion).
+#[utoipa::path(
+    get,
+    path = "/series/{slug}/export.md",
+    params(("slug" = String, Path, description = "Series slug")),
+    responses(
+        (status = 200, content_type = "text/markdown", body = String),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+pub async fn series_markdown(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let (series, episodes) = series_with_episodes(&state, &slug).await?;
+    let body = export::markdown_note(&series, &episodes);
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        body,
+    )
+        .into_response())
+}
+
+/// A series as a Markdown episode guide with type markers and a canon
+/// checklist, for pasting into Discord, Reddit or Notion.
+#[utoipa::path(
+    get,
+    path = "/series/{slug}/guide.md",
+    params(("slug" = String, Path, description = "Series slug")),
+    responses(
+        (status = 200, content_type = "text/markdown", body = String),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+pub async fn series_guide(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let (series, episodes) = series_with_episodes(&state, &slug).await?;
+    let body = export::episode_guide(&series, &episodes);
+    let disposition = format!("attachment; filename=\"{}-guide.md\"", series.slug);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Filler episodes as compact ranges (`26-32, 50, 90-110`), one line of
+/// plain text for skip scripts.
+#[utoipa::path(
+    get,
+    path = "/series/{slug}/filler.txt",
+    params(("slug" = String, Path, description = "Series slug")),
+    responses(
+        (status = 200, content_type = "text/plain", body = String),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+pub async fn filler_text(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let (_, episodes) = series_with_episodes(&state, &slug).await?;
+    let body = FillerRanges::from_episodes(&episodes).text + "\n";
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response())
+}
+
+/// Filler episodes as compact ranges, structured and as text.
+#[utoipa::path(
+    get,
+    path = "/series/{slug}/filler.json",
+    params(("slug" = String, Path, description = "Series slug")),
+    responses(
+        (status = 200, body = FillerRanges),
+        (status = 400, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    )
+)]
+pub async fn filler_json(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Json<FillerRanges>, ApiError> {
+    let (_, episodes) = series_with_episodes(&state, &slug).await?;
+    Ok(Json(FillerRanges::from_episodes(&episodes)))
+}
+
+/// Every series, episode and manual correction as one JSON document.
+#[utoipa::path(
+    get,
+    path = "/export/snapshot.json",
+    responses((status = 200, body = DatabaseSnapshot))
+)]
+pub async fn snapshot(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let snapshot = SeriesStore::export_all(&state.db).await?;
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"seiten-snapshot.json\"",
+        )],
+        Json(snapshot),
+    )
+        .into_response())
+}
+
+/// Restores a snapshot. Series in it replace the stored ones with the same
+/// slug, all in one transaction. Only allowed with `admin.enabled` set.
+#[utoipa::path(
+    post,
+    path = "/import/snapshot.json",
+    request_body = DatabaseSnapshot,
+    responses(
+        (status = 200, body = ImportReport),
+        (status = 400, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    )
+)]
+pub async fn restore_snapshot(
+    State(state): State<AppState>,
+    Json(snapshot): Json<DatabaseSnapshot>,
+) -> Result<Json<ImportReport>, ApiError> {
+    if !state.config.admin.enabled {
+        return Err(ApiError::Forbidden(
+            "snapshot restores are disabled; set admin.enabled in config.toml".to_string(),
+        ));
+    }
+    snapshot.validate().map_err(ApiError::BadRequest)?;
+    let report = SeriesStore::import_all(&state.db, &snapshot).await?;
+    Ok(Json(report))
+}
+
+/// The whole library as a zip of Markdown notes, ready to drop into a vault.
+#[utoipa::path(
+    get,
+    path = "/export/vault.zip",
+    responses((status = 200, content_type = "application/zip", body = Vec<u8>))
+)]
+pub async fn vault(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let export_err = |err: std::io::Error| ApiError::Export(err.to_string());
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    for series in SeriesStore::all(&state.db).await? {
+        let episodes = EpisodeStore::for_series(&state.db, series.id).await?;
+        let episodes: Vec<EpisodeData> = episodes.into_iter().map(EpisodeData::from).collect();
+        let series = SeriesData::from(series);
+        let note = export::markdown_note(&series, &episodes);
+
+        zip.start_file(format!("seiten/{}.md", series.slug), options)
+            .map_err(|err| ApiError::Export(err.to_string()))?;
+        zip.write_all(note.as_bytes()).map_err(export_err)?;
+    }
+
+    let bytes = zip
+        .finish()
+        .map_err(|err| ApiError::Export(err.to_string()))?
+        .into_inner();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"seiten-vault.zip\"",
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// `Write` sink that is drained after every file, so a library archive can
+/// be sent while it is still being built.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes one series per step, then the manifest.
+struct LibraryArchive {
+    db: DatabaseConnection,
+    zip: ZipWriter<StreamWriter<SharedBuffer>>,
+    buffer: SharedBuffer,
+    pending: std::vec::IntoIter<series::Model>,
+    manifest: LibraryManifest,
+}
+
+impl LibraryArchive {
+    /// Returns the bytes produced by the next step, and the archive if there
+    /// is more to write.
+    async fn next_chunk(mut self) -> io::Result<(Vec<u8>, Option<Self>)> {
+        let options = SimpleFileOptions::default();
+
+        let Some(series) = self.pending.next() else {
+            let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+            self.zip.start_file("manifest.json", options).map_err(io::Error::other)?;
+            self.zip.write_all(&manifest)?;
+            self.zip.finish().map_err(io::Error::other)?;
+            return Ok((self.buffer.take(), None));
+        };
+
+        let episodes = EpisodeStore::for_series(&self.db, series.id)
+            .await
+            .map_err(io::Error::other)?;
+        let export = SeriesExport {
+            series: series.into(),
+            episodes: episodes.into_iter().map(EpisodeData::from).collect(),
+        };
+        let file = format!("series/{}.json", export.series.slug);
+
+        self.zip.start_file(file.as_str(), options).map_err(io::Error::other)?;
+        self.zip.write_all(&serde_json::to_vec_pretty(&export)?)?;
+        self.manifest.series.push(ManifestEntry {
+            slug: export.series.slug,
+            title: export.series.title,
+            file,
+            episodes: export.episodes.len(),
+        });
+
+        Ok((self.buffer.take(), Some(self)))
+    }
+}
+
+/// Portable backup of the library: one JSON file per series plus a
+/// versioned `manifest.json`, streamed as it is generated.
+#[utoipa::path(
+    get,
+    path = "/export/library.zip",
+    responses((status = 200, content_type = "application/zip", body = Vec<u8>))
+)]
+pub async fn library(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let buffer = SharedBuffer::default();
+    let archive = LibraryArchive {
+        pending: SeriesStore::all(&state.db).await?.into_iter(),
+        db: state.db,
+        zip: ZipWriter::new_stream(buffer.clone()),
+        buffer,
+        manifest: LibraryManifest {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now(),
+            series: Vec::new(),
+        },
+    };
+
+    let chunks = futures::stream::unfold(Some(archive), |archive| async move {
+        match archive?.next_chunk().await {
+            Ok((bytes, rest)) => Some((Ok(bytes), rest)),
+            Err(err) => {
+                log::error!("library export failed: {err}");
+                Some((Err(err), None))
+            }
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"seiten-library.zip\"",
+            ),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response())
+}
@@ -0,0 +1,72 @@
+use app::{base_path, shell};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::Uri,
+    response::{IntoResponse, Redirect, Response},
+};
+use leptos::prelude::LeptosOptions;
+
+use crate::state::AppState;
+
+/// `path` with `prefix` removed, if `prefix` is a whole leading segment of
+/// it: `/seiten/pkg` and `/seiten?x` match `/seiten`, `/seitenfoo` does not.
+pub fn strip_segment_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    path.strip_prefix(prefix)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with('?'))
+}
+
+/// Page routes are registered with the base path already applied (the
+/// Leptos router knows about it), but server functions and static assets are
+/// looked up by their root-relative path, so the prefix is dropped first.
+fn strip_base_path(mut req: Request<Body>) -> Request<Body> {
+    let base = base_path();
+    if base.is_empty() {
+        return req;
+    }
+
+    let Some(path_and_query) = req.uri().path_and_query() else {
+        return req;
+    };
+    let Some(rest) = strip_segment_prefix(path_and_query.as_str(), base) else {
+        return req;
+    };
+    let rest = if rest.is_empty() || rest.starts_with('?') {
+        format!("/{rest}")
+    } else {
+        rest.to_string()
+    };
+
+    if let Ok(uri) = rest.parse::<Uri>() {
+        *req.uri_mut() = uri;
+    }
+    req
+}
+
+/// The home page is registered at the bare base path; proxies usually
+/// forward it with a trailing slash.
+pub async fn redirect_to_base() -> Redirect {
+    Redirect::permanent(base_path())
+}
+
+/// Server function endpoint for requests made under the base path.
+pub async fn server_fn_handler(
+    State(state): State<AppState>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    leptos_axum::handle_server_fns_with_context(
+        move || state.provide_context(),
+        strip_base_path(req),
+    )
+    .await
+}
+
+/// Static files and error pages, resolved relative to the base path.
+pub async fn file_and_error_handler(
+    State(options): State<LeptosOptions>,
+    req: Request<Body>,
+) -> Response {
+    let req = strip_base_path(req);
+    let uri = req.uri().clone();
+    leptos_axum::file_and_error_handler(shell)(uri, State(options), req).await
+}
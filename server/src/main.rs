@@ -3,19 +3,31 @@ use leptos::prelude::*;
 use leptos_axum::{generate_route_list, LeptosRoutes};
 use app::*;
 use leptos::logging::log;
-use sea_orm::{Database, EntityTrait, Set, ActiveModelTrait, ColumnTrait, QueryFilter};
+use std::time::Duration;
+use sea_orm::{EntityTrait, Set, ActiveModelTrait, ColumnTrait, QueryFilter};
 use sea_orm::entity::prelude::Uuid;
 use entity::prelude::*;
 use entity::{series, episode};
 
+mod api;
+mod base_path;
+mod security;
+mod shutdown;
+mod state;
+
+use state::AppState;
+
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
 
-    let db_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
-    log!("Connecting to database: {}", db_url);
-    let db = &Database::connect(&db_url)
+    let config = app::config::Config::load().expect("Failed to load configuration");
+    if config.database_url.is_empty() {
+        panic!("database_url must be set in config.toml, or DATABASE_URL in the environment");
+    }
+    let db_url = config.database_url.clone();
+    log!("Connecting to database: {}", config.redacted().database_url);
+    let db = &app::db::connect(&db_url)
         .await
         .expect("Failed to connect to database");
     log!("Database connected successfully");
@@ -29,7 +41,7 @@ async fn main() {
     //     .await
     //     .expect("Failed to apply schema");
 
-    db.get_schema_registry("entity::*").sync(db)
+    app::db::sync_schema(db)
         .await
         .expect("Failed to sync schema");
     log!("Schema sync completed");
@@ -43,9 +55,8 @@ async fn main() {
         .await
         .unwrap();
 
-    let series_id = if let Some(series) = existing_series {
+    if existing_series.is_some() {
         log!("Series 'One Piece' already exists, skipping...");
-        series.id
     } else {
         // Create new series
         let series_id = Uuid::new_v4();
@@ -54,7 +65,6 @@ async fn main() {
             slug: Set("one-piece".to_string()),
             title: Set("One Piece".to_string()),
             last_fetched: Set(None),
-            ..Default::default()
         };
         one_piece.insert(db).await.unwrap();
         log!("Created series: One Piece");
@@ -78,29 +88,94 @@ async fn main() {
             ep.insert(db).await.unwrap();
             log!("Created episode {}: {}", num, title);
         }
+    }
 
-        series_id
-    };
+    let filled = app::db::EpisodeStore::backfill_permalinks(db)
+        .await
+        .expect("Failed to backfill episode permalinks");
+    if filled > 0 {
+        log!("Backfilled permalinks for {} episodes", filled);
+    }
 
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
     let leptos_options = conf.leptos_options;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
+    let state = AppState {
+        leptos_options: leptos_options.clone(),
+        db: db.clone(),
+        config: std::sync::Arc::new(config),
+    };
 
-    let app = Router::new()
-        .leptos_routes(&leptos_options, routes, {
-            let leptos_options = leptos_options.clone();
-            move || shell(leptos_options.clone())
-        })
-        .fallback(leptos_axum::file_and_error_handler(shell))
-        .with_state(leptos_options);
+    let base = app::base_path();
+    let mut app = Router::new()
+        .nest(&format!("{base}/api/v1"), api::routes())
+        .merge(api::docs(base))
+        .leptos_routes_with_context(
+            &state,
+            routes,
+            {
+                let state = state.clone();
+                move || state.provide_context()
+            },
+            {
+                let leptos_options = leptos_options.clone();
+                move || shell(leptos_options.clone())
+            },
+        );
+    if !base.is_empty() {
+        app = app
+            .route(&format!("{base}/"), axum::routing::get(base_path::redirect_to_base))
+            .route(
+                &format!("{base}/api/{{*fn_name}}"),
+                axum::routing::any(base_path::server_fn_handler),
+            );
+    }
+    let grace = Duration::from_secs(state.config.server.shutdown_grace_secs);
+    let app = app
+        .fallback(base_path::file_and_error_handler)
+        .layer(axum::middleware::from_fn_with_state(
+            security::SecurityHeaders::from_config(&state.config.security),
+            security::security_headers,
+        ))
+        .with_state(state);
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
     log!("listening on http://{}", &addr);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    let (stop_tx, mut stop_rx) = tokio::sync::watch::channel(());
+    let mut server = tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .with_graceful_shutdown(async move {
+                stop_rx.changed().await.ok();
+            })
+            .await
+    });
+
+    tokio::select! {
+        result = &mut server => {
+            result.unwrap().unwrap();
+            return;
+        }
+        () = shutdown::signal() => {}
+    }
+
+    // Stop accepting connections, then give in-flight requests (and the
+    // database writes they make) a bounded time to finish.
+    log!("Shutting down, waiting up to {}s for in-flight requests", grace.as_secs());
+    stop_tx.send(()).ok();
+    match tokio::time::timeout(grace, &mut server).await {
+        Ok(result) => result.unwrap().unwrap(),
+        Err(_) => {
+            log!("Requests still running after {}s, aborting them", grace.as_secs());
+            server.abort();
+        }
+    }
+
+    if let Err(err) = db.clone().close().await {
+        log!("Failed to close the database: {}", err);
+    }
+    log!("Shutdown complete");
 }
@@ -0,0 +1,92 @@
+use app::base_path;
+use app::config::SecurityConfig;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::base_path::strip_segment_prefix;
+
+/// Paths (under the base path) that are meant to be framed by other sites and
+/// therefore skip the framing restrictions.
+const EMBED_PREFIX: &str = "/embed";
+
+/// Security headers added to every response, switched by the `[security]`
+/// config section.
+#[derive(Clone, Debug)]
+pub struct SecurityHeaders {
+    pub csp: bool,
+    pub hsts: bool,
+}
+
+impl SecurityHeaders {
+    pub fn from_config(config: &SecurityConfig) -> Self {
+        Self {
+            csp: config.csp,
+            hsts: config.hsts,
+        }
+    }
+
+    /// Builds the policy for a response. Pages rendered by Leptos hand over
+    /// their per-request nonce so the inline hydration scripts are allowed
+    /// without resorting to `'unsafe-inline'`.
+    fn content_security_policy(&self, nonce: Option<&str>, embeddable: bool) -> String {
+        let nonce = nonce
+            .map(|nonce| format!(" 'nonce-{nonce}'"))
+            .unwrap_or_default();
+        let frame_ancestors = if embeddable { "*" } else { "'none'" };
+
+        format!(
+            "default-src 'self'; \
+             script-src 'self'{nonce} 'wasm-unsafe-eval'; \
+             style-src 'self'{nonce}; \
+             img-src 'self' data:; \
+             connect-src 'self' ws: wss:; \
+             object-src 'none'; \
+             base-uri 'self'; \
+             form-action 'self'; \
+             frame-ancestors {frame_ancestors}"
+        )
+    }
+}
+
+pub async fn security_headers(
+    State(config): State<SecurityHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let embed_prefix = format!("{}{EMBED_PREFIX}", base_path());
+    let embeddable = strip_segment_prefix(request.uri().path(), &embed_prefix).is_some();
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    // Never leak the nonce handoff header to the client.
+    let nonce = headers
+        .remove(app::NONCE_HEADER)
+        .and_then(|value| value.to_str().map(str::to_owned).ok());
+
+    if config.csp {
+        let policy = config.content_security_policy(nonce.as_deref(), embeddable);
+        if let Ok(value) = HeaderValue::from_str(&policy) {
+            headers.insert(header::CONTENT_SECURITY_POLICY, value);
+        }
+    }
+    if !embeddable {
+        headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    }
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("strict-origin-when-cross-origin"),
+    );
+    if config.hsts {
+        headers.insert(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=31536000; includeSubDomains"),
+        );
+    }
+
+    response
+}
@@ -0,0 +1,25 @@
+use leptos::logging::log;
+
+/// Resolves on Ctrl-C, or on SIGTERM (what `docker stop` sends) on Unix.
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => log!("Received Ctrl-C"),
+        () = terminate => log!("Received SIGTERM"),
+    }
+}
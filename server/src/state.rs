@@ -0,0 +1,27 @@
+use app::config::Config;
+use axum::extract::FromRef;
+use leptos::prelude::LeptosOptions;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+
+/// Shared state handed to every axum handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub leptos_options: LeptosOptions,
+    pub db: DatabaseConnection,
+    pub config: Arc<Config>,
+}
+
+impl AppState {
+    /// Context made available to server functions and SSR rendering.
+    pub fn provide_context(&self) {
+        leptos::prelude::provide_context(self.db.clone());
+        leptos::prelude::provide_context(self.config.clone());
+    }
+}
+
+impl FromRef<AppState> for LeptosOptions {
+    fn from_ref(state: &AppState) -> Self {
+        state.leptos_options.clone()
+    }
+}